@@ -1,16 +1,23 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::keccak;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use ed25519_dalek::{PublicKey as Ed25519PubKey, Signature, Verifier};
 use pyth_client::Price;
 
 declare_id!("Presale1111111111111111111111111111111111111");
 
+pub const MAX_ROUNDS: usize = 8;
+pub const MAX_OWNERS: usize = 10;
+
 #[program]
 pub mod myxen_presale {
     use super::*;
 
     pub fn initialize(ctx: Context<Initialize>, params: InitializeParams) -> Result<()> {
         let cfg = &mut ctx.accounts.config;
+        cfg.owner = ctx.accounts.payer.key();
+        cfg.pending_owner = Pubkey::default();
         cfg.token_mint = params.token_mint;
         cfg.treasury = params.treasury;
         cfg.total_presale_tokens = params.total_presale_tokens;
@@ -22,22 +29,121 @@ pub mod myxen_presale {
         cfg.accepted_spl_mints = params.accepted_spl_mints;
         cfg.accepted_spl_mint_count = 4u8;
         cfg.oracle_pubkey = params.oracle_pubkey;
+        require!(
+            params.tge_unlock_bps <= 10000 && params.daily_release_bps <= 10000,
+            PresaleError::InvalidVestingSchedule
+        );
+        cfg.tge_unlock_bps = params.tge_unlock_bps;
+        cfg.cliff_seconds = params.cliff_seconds;
         cfg.daily_release_bps = params.daily_release_bps;
         cfg.vesting_days = params.vesting_days;
         cfg.fee_bps = params.fee_bps;
+        require!(
+            params.extension_window_secs >= 0 && params.extension_secs >= 0,
+            PresaleError::InvalidRounds
+        );
+        cfg.extension_window_secs = params.extension_window_secs;
+        cfg.extension_secs = params.extension_secs;
+        cfg.extension_min_usd = params.extension_min_usd;
+        cfg.max_end_ts = params.max_end_ts;
+        cfg.oversubscription = params.oversubscription;
         cfg.paused = false;
         Ok(())
     }
 
+    pub fn configure_rounds(ctx: Context<ConfigureRounds>, rounds: Vec<SaleRound>) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
+        let config = &ctx.accounts.config;
+        let sale = &mut ctx.accounts.sale_state;
+        // rounds can only be reshaped before anything has been sold
+        require!(sale.sold_tokens == 0, PresaleError::SaleAlreadyStarted);
+        require!(
+            !rounds.is_empty() && rounds.len() <= MAX_ROUNDS,
+            PresaleError::InvalidRounds
+        );
+
+        let mut private_total = 0u64;
+        let mut public_total = 0u64;
+        let mut prev_end = i64::MIN;
+        for round in rounds.iter() {
+            require!(
+                round.start_ts < round.end_ts && round.start_ts >= prev_end,
+                PresaleError::InvalidRounds
+            );
+            require!(
+                round.price_usd_micro > 0 && round.token_cap > 0 && round.sold_tokens == 0,
+                PresaleError::InvalidRounds
+            );
+            prev_end = round.end_ts;
+            require!(
+                round.end_ts <= config.max_end_ts,
+                PresaleError::InvalidRounds
+            );
+            match round.kind {
+                RoundKind::Private => {
+                    private_total = private_total
+                        .checked_add(round.token_cap)
+                        .ok_or(PresaleError::Overflow)?
+                }
+                RoundKind::Public => {
+                    public_total = public_total
+                        .checked_add(round.token_cap)
+                        .ok_or(PresaleError::Overflow)?
+                }
+            }
+        }
+        require!(
+            private_total <= config.private_allocation && public_total <= config.public_allocation,
+            PresaleError::AllocationExceeded
+        );
+        require!(
+            private_total
+                .checked_add(public_total)
+                .ok_or(PresaleError::Overflow)?
+                <= config.total_presale_tokens,
+            PresaleError::AllocationExceeded
+        );
+
+        sale.round_count = rounds.len() as u8;
+        sale.phase = 0;
+        for (i, round) in rounds.into_iter().enumerate() {
+            sale.rounds[i] = round;
+        }
+
+        emit!(RoundsConfigured {
+            round_count: sale.round_count
+        });
+        Ok(())
+    }
+
     pub fn buy(
         ctx: Context<Buy>,
         amount_paid: u64,
         currency: Currency,
         attestation: Option<Attestation>,
+        whitelist: Option<WhitelistProof>,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
         require!(!config.paused, PresaleError::Paused);
 
+        // resolve the active round, advancing the phase if time has moved on
+        let sale = &mut ctx.accounts.sale_state;
+        require!(!sale.finalized, PresaleError::SaleFinalized);
+        let now = Clock::get()?.unix_timestamp;
+        let round_idx = sale.active_round(now).ok_or(PresaleError::RoundNotActive)?;
+        if sale.phase as usize != round_idx {
+            emit!(PhaseAdvanced {
+                from: sale.phase,
+                to: round_idx as u8
+            });
+            sale.phase = round_idx as u8;
+        }
+
         // Basic per-wallet cap enforcement using contribution stored in contributor account
         let contributor = &mut ctx.accounts.contributor;
         let usd_value = match currency {
@@ -68,26 +174,84 @@ pub mod myxen_presale {
             .contribution_usd
             .checked_add(usd_value)
             .ok_or(PresaleError::Overflow)?;
-        require!(
-            projected_usd <= config.max_per_wallet_usd,
-            PresaleError::PerWalletCapExceeded
-        );
+        let projected_round_usd = contributor.round_contributions_usd[round_idx]
+            .checked_add(usd_value)
+            .ok_or(PresaleError::Overflow)?;
+        let round = &mut sale.rounds[round_idx];
 
-        // tokens = USD / price -> using fixed point: price in micro-dollars (1e6). To keep simple here, we assume price given as USD per token in micro USD.
-        let tokens =
-            ((usd_value as u128 * 1_000_000u128) / (config.presale_price_usd_micro as u128)) as u64;
+        if round.kind == RoundKind::Private {
+            // private rounds: the partner's proven allocation replaces the wallet caps
+            let proof = whitelist.ok_or(PresaleError::WhitelistProofRequired)?;
+            require!(
+                verify_whitelist_proof(
+                    &config.whitelist_root,
+                    &ctx.accounts.payer.key(),
+                    proof.max_allocation_usd,
+                    &proof.proof
+                ),
+                PresaleError::InvalidWhitelistProof
+            );
+            require!(
+                projected_round_usd <= proof.max_allocation_usd,
+                PresaleError::PerWalletCapExceeded
+            );
+        } else {
+            require!(
+                projected_usd <= config.max_per_wallet_usd,
+                PresaleError::PerWalletCapExceeded
+            );
+            // enforce the active round's per-wallet cap
+            require!(
+                projected_round_usd <= round.wallet_cap_usd,
+                PresaleError::PerWalletCapExceeded
+            );
+        }
 
-        // reserve tokens in SaleState
-        let sale = &mut ctx.accounts.sale_state;
+        // tokens = USD / price -> using fixed point: price in micro-dollars (1e6), at the active round's price.
+        let tokens = ((usd_value as u128 * 1_000_000u128) / (round.price_usd_micro as u128)) as u64;
+
+        // reserve tokens against the round cap and the overall sale
+        let new_round_sold = round
+            .sold_tokens
+            .checked_add(tokens)
+            .ok_or(PresaleError::Overflow)?;
+        // fair launch sales accept buys past the caps and pro-rate at finalize
+        require!(
+            config.oversubscription || new_round_sold <= round.token_cap,
+            PresaleError::PhaseCapExceeded
+        );
         let new_sold = sale
             .sold_tokens
             .checked_add(tokens)
             .ok_or(PresaleError::Overflow)?;
         require!(
-            new_sold <= config.total_presale_tokens,
+            config.oversubscription || new_sold <= config.total_presale_tokens,
             PresaleError::PhaseCapExceeded
         );
+        sale.rounds[round_idx].sold_tokens = new_round_sold;
         sale.sold_tokens = new_sold;
+        contributor.round_contributions_usd[round_idx] = projected_round_usd;
+
+        // a large buy late in the final round pushes the sale end out, up to max_end_ts
+        let is_final_round = round_idx + 1 == sale.round_count as usize;
+        let round = &mut sale.rounds[round_idx];
+        if is_final_round
+            && config.extension_secs > 0
+            && usd_value >= config.extension_min_usd
+            && round.end_ts - now <= config.extension_window_secs
+            && round.end_ts < config.max_end_ts
+        {
+            round.end_ts = round
+                .end_ts
+                .checked_add(config.extension_secs)
+                .ok_or(PresaleError::Overflow)?
+                .min(config.max_end_ts);
+            emit!(SaleExtended {
+                contributor: ctx.accounts.payer.key(),
+                usd_value,
+                end_ts: round.end_ts
+            });
+        }
 
         // update contributor
         contributor.contribution_amount_native = contributor
@@ -102,22 +266,27 @@ pub mod myxen_presale {
             .claimable_tokens
             .checked_add(tokens)
             .ok_or(PresaleError::Overflow)?;
-        contributor.vesting_start_ts = Clock::get()?.unix_timestamp as u64;
 
-        // Transfer funds to treasury: for SPL currency we would transfer tokens, for SOL we transfer lamports
-        // Implementation detail: we prefer immediate transfer to treasury; implement using CPI for SPL and lamport transfer for SOL
+        // Proceeds are held in program escrows and leave only through approved withdrawal proposals
         match currency {
             Currency::SOL => {
-                **ctx
-                    .accounts
-                    .treasury
-                    .to_account_info()
-                    .try_borrow_mut_lamports()? += amount_paid;
-                **ctx
-                    .accounts
-                    .payer
-                    .to_account_info()
-                    .try_borrow_mut_lamports()? -= amount_paid;
+                let cpi_accounts = anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.sol_escrow.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.system_program.to_account_info();
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(cpi_program, cpi_accounts),
+                    amount_paid,
+                )?;
+                contributor.contribution_lamports = contributor
+                    .contribution_lamports
+                    .checked_add(amount_paid)
+                    .ok_or(PresaleError::Overflow)?;
+                sale.raised_lamports = sale
+                    .raised_lamports
+                    .checked_add(amount_paid)
+                    .ok_or(PresaleError::Overflow)?;
             }
             Currency::SPL => {
                 // verify payment token account provided
@@ -129,23 +298,38 @@ pub mod myxen_presale {
 
                 // verify mint is accepted
                 let mint = payment_acc.mint;
-                let mut ok = false;
-                for i in 0..(ctx.accounts.config.accepted_spl_mint_count as usize) {
-                    if ctx.accounts.config.accepted_spl_mints[i] == mint {
-                        ok = true;
-                        break;
-                    }
-                }
-                require!(ok, PresaleError::UnsupportedSPLMint);
+                let slot = ctx
+                    .accounts
+                    .config
+                    .spl_mint_slot(&mint)
+                    .ok_or(error!(PresaleError::UnsupportedSPLMint))?;
+
+                // the escrow is the config PDA's ATA for the payment mint
+                let escrow_acc = ctx
+                    .accounts
+                    .escrow_token_account
+                    .as_ref()
+                    .ok_or(error!(PresaleError::PaymentAccountMissing))?;
+                require!(
+                    escrow_acc.key()
+                        == get_associated_token_address(&ctx.accounts.config.key(), &mint),
+                    PresaleError::InvalidEscrow
+                );
 
                 // Transfer SPL using Token program
                 let cpi_accounts = Transfer {
                     from: payment_acc.to_account_info(),
-                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    to: escrow_acc.to_account_info(),
                     authority: ctx.accounts.payer.to_account_info(),
                 };
                 let cpi_program = ctx.accounts.token_program.to_account_info();
                 token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount_paid)?;
+                contributor.contribution_spl[slot] = contributor.contribution_spl[slot]
+                    .checked_add(amount_paid)
+                    .ok_or(PresaleError::Overflow)?;
+                sale.raised_spl[slot] = sale.raised_spl[slot]
+                    .checked_add(amount_paid)
+                    .ok_or(PresaleError::Overflow)?;
             }
         }
 
@@ -153,7 +337,7 @@ pub mod myxen_presale {
             contributor: ctx.accounts.payer.key(),
             usd_value,
             tokens_reserved: tokens,
-            phase: sale.phase as u8,
+            phase: round_idx as u8,
             tx_hash: [0u8; 32]
         });
 
@@ -162,32 +346,60 @@ pub mod myxen_presale {
 
     pub fn claim(ctx: Context<Claim>) -> Result<()> {
         let contributor = &mut ctx.accounts.contributor;
-        let now = Clock::get()?.unix_timestamp as u64;
-        let start = contributor.vesting_start_ts;
-        require!(start > 0, PresaleError::NoVesting);
-
-        let elapsed_days = (now - start) / 86400u64;
-        let days = contributor.vesting_days.min(elapsed_days as u64);
+        let config = &ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+        // vesting runs from TGE, set once by the owner when the sale is finalized
+        require!(config.tge_timestamp > 0, PresaleError::NoVesting);
 
-        let total = contributor
-            .claimable_tokens
-            .checked_add(contributor.claimed_tokens)
-            .ok_or(PresaleError::Overflow)?;
-        let daily_bps = contributor.daily_release_bps as u128;
-        let mut vested = 0u128;
-        if days > 0 {
-            vested = (total as u128 * daily_bps * days as u128) / 10000u128;
-        }
+        // an oversubscribed sale scales every allocation down to the hard cap
+        let total = ctx.accounts.sale_state.filled(
+            contributor
+                .claimable_tokens
+                .checked_add(contributor.claimed_tokens)
+                .ok_or(PresaleError::Overflow)?,
+        );
+        let vested = tge_vested_amount(
+            total,
+            config.tge_timestamp,
+            now,
+            config.tge_unlock_bps,
+            config.cliff_seconds,
+            config.daily_release_bps,
+            config.vesting_days as u64,
+        );
 
-        let vested_u64 = vested as u64;
-        let claimable = vested_u64
-            .checked_sub(contributor.claimed_tokens)
-            .unwrap_or(0);
+        let claimable = vested.checked_sub(contributor.claimed_tokens).unwrap_or(0);
         require!(claimable > 0, PresaleError::NothingToClaim);
 
-        // Transfer tokens (assumes program has token vault funded)
-        // TODO: implement CPI to transfer token from vault to recipient
+        // Transfer tokens from the config-owned vault to the contributor's ATA
+        let bump = *ctx.bumps.get("config").unwrap();
+        let seeds = &[
+            b"presale-config".as_ref(),
+            config.token_mint.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            claimable,
+        )?;
 
+        contributor.claimable_tokens = contributor
+            .claimable_tokens
+            .checked_sub(claimable)
+            .ok_or(PresaleError::Overflow)?;
+        ctx.accounts.config.total_claimed = ctx
+            .accounts
+            .config
+            .total_claimed
+            .checked_add(claimable)
+            .ok_or(PresaleError::Overflow)?;
         contributor.claimed_tokens = contributor
             .claimed_tokens
             .checked_add(claimable)
@@ -196,34 +408,517 @@ pub mod myxen_presale {
         emit!(VestingClaimed {
             contributor: ctx.accounts.payer.key(),
             amount: claimable,
-            timestamp: now
+            timestamp: now as u64
         });
 
         Ok(())
     }
 
-    pub fn withdraw_funds(ctx: Context<WithdrawFunds>) -> Result<()> {
-        // Only multisig allowed — enforced by signer requirement on config.owner
-        let amount = **ctx.accounts.escrow.to_account_info().lamports.borrow();
-        **ctx
-            .accounts
-            .escrow
-            .to_account_info()
-            .try_borrow_mut_lamports()? -= amount;
-        **ctx
+    pub fn fund_vault(ctx: Context<FundVault>, amount: u64) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
+        require!(amount > 0, PresaleError::InvalidAmount);
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        ctx.accounts.vault.reload()?;
+        emit!(VaultFunded {
+            amount,
+            vault_balance: ctx.accounts.vault.amount
+        });
+        Ok(())
+    }
+
+    pub fn check_solvency(ctx: Context<CheckSolvency>) -> Result<()> {
+        let vault_balance = ctx.accounts.vault.amount;
+        let outstanding = ctx
             .accounts
-            .treasury
-            .to_account_info()
-            .try_borrow_mut_lamports()? += amount;
-        emit!(FundsTransferredToTreasury {
+            .sale_state
+            .sold_tokens
+            .checked_sub(ctx.accounts.config.total_claimed)
+            .ok_or(PresaleError::Overflow)?;
+        let solvent = vault_balance >= outstanding;
+        // emit before asserting so simulations report the figures either way
+        emit!(SolvencyChecked {
+            vault_balance,
+            outstanding,
+            solvent
+        });
+        require!(solvent, PresaleError::VaultInsolvent);
+        Ok(())
+    }
+
+    /// One-time setup of the M-of-N owner set that approves treasury withdrawals;
+    /// later changes go through `update_owner_set`
+    pub fn configure_owners(
+        ctx: Context<ConfigureOwners>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+        timelock_seconds: i64,
+    ) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
+        let owner_set = &mut ctx.accounts.owner_set;
+        require!(
+            owner_set.threshold == 0,
+            PresaleError::OwnersAlreadyConfigured
+        );
+        require!(
+            OwnerSet::is_valid(&owners, threshold, timelock_seconds),
+            PresaleError::InvalidOwnerSet
+        );
+        owner_set.config = ctx.accounts.config.key();
+        owner_set.owners = owners;
+        owner_set.threshold = threshold;
+        owner_set.timelock_seconds = timelock_seconds;
+        owner_set.proposal_count = 0;
+        owner_set.bump = *ctx.bumps.get("owner_set").unwrap();
+        emit!(OwnersConfigured {
+            owners: owner_set.owners.clone(),
+            threshold,
+            timelock_seconds
+        });
+        Ok(())
+    }
+
+    /// Replace the owners, threshold and timelock, signed by the current threshold of owners
+    ///
+    /// The co-signing owners follow the signer in the remaining accounts.
+    pub fn update_owner_set(
+        ctx: Context<UpdateOwnerSet>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+        timelock_seconds: i64,
+    ) -> Result<()> {
+        let owner_set = &mut ctx.accounts.owner_set;
+        require!(
+            owner_set.is_approved(&ctx.accounts.owner, ctx.remaining_accounts),
+            PresaleError::ThresholdNotMet
+        );
+        require!(
+            OwnerSet::is_valid(&owners, threshold, timelock_seconds),
+            PresaleError::InvalidOwnerSet
+        );
+        owner_set.owners = owners;
+        owner_set.threshold = threshold;
+        owner_set.timelock_seconds = timelock_seconds;
+        emit!(OwnersConfigured {
+            owners: owner_set.owners.clone(),
+            threshold,
+            timelock_seconds
+        });
+        Ok(())
+    }
+
+    /// Open a withdrawal proposal; the proposer's approval is counted immediately
+    pub fn propose_withdrawal(
+        ctx: Context<ProposeWithdrawal>,
+        currency: Currency,
+        mint: Pubkey,
+        amount: u64,
+        destination: Pubkey,
+    ) -> Result<()> {
+        let owner_set = &mut ctx.accounts.owner_set;
+        let proposer = ctx.accounts.proposer.key();
+        require!(
+            owner_set.owners.contains(&proposer),
+            PresaleError::NotAnOwner
+        );
+        require!(amount > 0, PresaleError::InvalidAmount);
+        if currency == Currency::SPL {
+            require!(
+                ctx.accounts.config.accepts_spl_mint(&mint),
+                PresaleError::UnsupportedSPLMint
+            );
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.config = ctx.accounts.config.key();
+        proposal.id = owner_set.proposal_count;
+        proposal.proposer = proposer;
+        // for SPL, destination is the receiving token account
+        proposal.currency = currency;
+        proposal.mint = mint;
+        proposal.amount = amount;
+        proposal.destination = destination;
+        proposal.approvals = vec![proposer];
+        proposal.eta = now
+            .checked_add(owner_set.timelock_seconds)
+            .ok_or(PresaleError::Overflow)?;
+        proposal.executed = false;
+        proposal.bump = *ctx.bumps.get("proposal").unwrap();
+        owner_set.proposal_count = owner_set
+            .proposal_count
+            .checked_add(1)
+            .ok_or(PresaleError::Overflow)?;
+
+        emit!(WithdrawalProposed {
+            id: proposal.id,
+            proposer,
+            currency: proposal.currency.clone() as u8,
+            mint,
             amount,
-            currency: 0u8,
+            destination,
+            eta: proposal.eta
+        });
+        Ok(())
+    }
+
+    pub fn approve_withdrawal(ctx: Context<ApproveWithdrawal>) -> Result<()> {
+        let owner_set = &ctx.accounts.owner_set;
+        let proposal = &mut ctx.accounts.proposal;
+        let approver = ctx.accounts.approver.key();
+        require!(
+            owner_set.owners.contains(&approver),
+            PresaleError::NotAnOwner
+        );
+        require!(!proposal.executed, PresaleError::ProposalExecuted);
+        require!(
+            !proposal.approvals.contains(&approver),
+            PresaleError::AlreadyApproved
+        );
+        proposal.approvals.push(approver);
+        emit!(WithdrawalApproved {
+            id: proposal.id,
+            approver,
+            approvals: proposal.approvals.len() as u8
+        });
+        Ok(())
+    }
+
+    /// Pay out an approved proposal once the threshold is met and the timelock has elapsed
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        let owner_set = &ctx.accounts.owner_set;
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            owner_set.owners.contains(&ctx.accounts.executor.key()),
+            PresaleError::NotAnOwner
+        );
+        require!(!proposal.executed, PresaleError::ProposalExecuted);
+        // only approvals from current owners count
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|a| owner_set.owners.contains(a))
+            .count();
+        require!(
+            approvals >= owner_set.threshold as usize,
+            PresaleError::ThresholdNotMet
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.eta,
+            PresaleError::TimelockActive
+        );
+
+        require!(
+            ctx.accounts.destination.key() == proposal.destination,
+            PresaleError::InvalidDestination
+        );
+        // a fair launch only knows what it owes back once finalized
+        let sale = &ctx.accounts.sale_state;
+        require!(
+            !ctx.accounts.config.oversubscription || sale.finalized,
+            PresaleError::SaleNotFinalized
+        );
+        match proposal.currency {
+            Currency::SOL => {
+                require!(
+                    ctx.accounts
+                        .sol_escrow
+                        .lamports()
+                        .checked_sub(proposal.amount)
+                        .map_or(false, |left| left >= sale.excess_lamports_owed),
+                    PresaleError::ExcessReserved
+                );
+                let config_key = ctx.accounts.config.key();
+                let bump = *ctx.bumps.get("sol_escrow").unwrap();
+                let seeds = &[b"sol-escrow".as_ref(), config_key.as_ref(), &[bump]];
+                let signer = &[&seeds[..]];
+                let cpi_accounts = anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.sol_escrow.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.system_program.to_account_info();
+                anchor_lang::system_program::transfer(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    proposal.amount,
+                )?;
+            }
+            Currency::SPL => {
+                let source = ctx
+                    .accounts
+                    .source_token_account
+                    .as_ref()
+                    .ok_or(PresaleError::MissingAccount)?;
+                // only sale proceeds can be withdrawn, never the MYXN claim inventory
+                require!(
+                    ctx.accounts.config.accepts_spl_mint(&proposal.mint)
+                        && source.key()
+                            == get_associated_token_address(
+                                &ctx.accounts.config.key(),
+                                &proposal.mint
+                            ),
+                    PresaleError::InvalidEscrow
+                );
+                let slot = ctx
+                    .accounts
+                    .config
+                    .spl_mint_slot(&proposal.mint)
+                    .ok_or(error!(PresaleError::UnsupportedSPLMint))?;
+                require!(
+                    source
+                        .amount
+                        .checked_sub(proposal.amount)
+                        .map_or(false, |left| left >= sale.excess_spl_owed[slot]),
+                    PresaleError::ExcessReserved
+                );
+                let bump = *ctx.bumps.get("config").unwrap();
+                let seeds = &[
+                    b"presale-config".as_ref(),
+                    ctx.accounts.config.token_mint.as_ref(),
+                    &[bump],
+                ];
+                let signer = &[&seeds[..]];
+                let cpi_accounts = Transfer {
+                    from: source.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.config.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                token::transfer(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    proposal.amount,
+                )?;
+            }
+        }
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.executed = true;
+        emit!(WithdrawalExecuted {
+            id: proposal.id,
+            executor: ctx.accounts.executor.key(),
+            amount: proposal.amount,
+            destination: proposal.destination
+        });
+        emit!(FundsTransferredToTreasury {
+            amount: proposal.amount,
+            currency: proposal.currency.clone() as u8,
             tx_hash: [0u8; 32]
         });
         Ok(())
     }
 
+    /// Drop a pending proposal, signed by the current threshold of owners; the rent goes back to the proposer
+    ///
+    /// The co-signing owners follow the signer in the remaining accounts.
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        require!(
+            ctx.accounts
+                .owner_set
+                .is_approved(&ctx.accounts.owner, ctx.remaining_accounts),
+            PresaleError::ThresholdNotMet
+        );
+        let proposal = &ctx.accounts.proposal;
+        require!(!proposal.executed, PresaleError::ProposalExecuted);
+        emit!(WithdrawalCancelled {
+            id: proposal.id,
+            canceller: ctx.accounts.owner.key()
+        });
+        Ok(())
+    }
+
+    /// Close the sale for good once the final round has ended or the sale is sold out; anyone may call
+    pub fn finalize_sale(ctx: Context<FinalizeSale>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let sale = &mut ctx.accounts.sale_state;
+        require!(!sale.finalized, PresaleError::SaleFinalized);
+        require!(sale.round_count > 0, PresaleError::InvalidRounds);
+        let now = Clock::get()?.unix_timestamp;
+        // fair launch sales always run to the end
+        require!(
+            now >= sale.end_ts()
+                || (!config.oversubscription && sale.sold_tokens >= config.total_presale_tokens),
+            PresaleError::SaleStillOpen
+        );
+        sale.finalized = true;
+
+        if sale.sold_tokens > config.total_presale_tokens {
+            // pro-rate to the cap and reserve the excess deposits for claim_excess
+            sale.total_requested = sale.sold_tokens;
+            sale.sold_tokens = config.total_presale_tokens;
+            sale.excess_lamports_owed = sale.excess(sale.raised_lamports);
+            for i in 0..sale.raised_spl.len() {
+                sale.excess_spl_owed[i] = sale.excess(sale.raised_spl[i]);
+            }
+            emit!(SaleOversubscribed {
+                total_requested: sale.total_requested,
+                sold_tokens: sale.sold_tokens
+            });
+        }
+        emit!(SaleFinalized {
+            sold_tokens: sale.sold_tokens,
+            timestamp: now
+        });
+        Ok(())
+    }
+
+    /// Return the unfilled part of a contributor's deposits after an oversubscribed sale
+    ///
+    /// Pays the SOL excess, plus the excess for one stablecoin when its escrow and
+    /// recipient token accounts are passed; each leg can be claimed once.
+    pub fn claim_excess(ctx: Context<ClaimExcess>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let sale = &ctx.accounts.sale_state;
+        require!(sale.finalized, PresaleError::SaleNotFinalized);
+        require!(sale.is_oversubscribed(), PresaleError::NotOversubscribed);
+        let contributor = &mut ctx.accounts.contributor;
+
+        let mut excess_lamports = 0;
+        if !contributor.excess_sol_claimed {
+            excess_lamports = sale.excess(contributor.contribution_lamports);
+            contributor.excess_sol_claimed = true;
+            if excess_lamports > 0 {
+                let config_key = config.key();
+                let bump = *ctx.bumps.get("sol_escrow").unwrap();
+                let seeds = &[b"sol-escrow".as_ref(), config_key.as_ref(), &[bump]];
+                let signer = &[&seeds[..]];
+                let cpi_accounts = anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.sol_escrow.to_account_info(),
+                    to: ctx.accounts.payer.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.system_program.to_account_info();
+                anchor_lang::system_program::transfer(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    excess_lamports,
+                )?;
+            }
+        }
+
+        let mut excess_tokens = 0;
+        let mut spl_slot = None;
+        if let (Some(escrow_acc), Some(recipient)) = (
+            ctx.accounts.escrow_token_account.as_ref(),
+            ctx.accounts.recipient_token_account.as_ref(),
+        ) {
+            let slot = config
+                .spl_mint_slot(&escrow_acc.mint)
+                .ok_or(error!(PresaleError::UnsupportedSPLMint))?;
+            require!(
+                escrow_acc.key() == get_associated_token_address(&config.key(), &escrow_acc.mint),
+                PresaleError::InvalidEscrow
+            );
+            require!(
+                recipient.owner == ctx.accounts.payer.key(),
+                PresaleError::InvalidDestination
+            );
+            require!(
+                !contributor.excess_spl_claimed[slot],
+                PresaleError::ExcessAlreadyClaimed
+            );
+            excess_tokens = sale.excess(contributor.contribution_spl[slot]);
+            contributor.excess_spl_claimed[slot] = true;
+            spl_slot = Some(slot);
+            if excess_tokens > 0 {
+                let bump = *ctx.bumps.get("config").unwrap();
+                let seeds = &[
+                    b"presale-config".as_ref(),
+                    config.token_mint.as_ref(),
+                    &[bump],
+                ];
+                let signer = &[&seeds[..]];
+                let cpi_accounts = Transfer {
+                    from: escrow_acc.to_account_info(),
+                    to: recipient.to_account_info(),
+                    authority: config.to_account_info(),
+                };
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                token::transfer(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+                    excess_tokens,
+                )?;
+            }
+        }
+        require!(
+            excess_lamports > 0 || excess_tokens > 0,
+            PresaleError::NothingToClaim
+        );
+
+        let sale = &mut ctx.accounts.sale_state;
+        sale.excess_lamports_owed = sale
+            .excess_lamports_owed
+            .checked_sub(excess_lamports)
+            .ok_or(PresaleError::Overflow)?;
+        if let Some(slot) = spl_slot {
+            sale.excess_spl_owed[slot] = sale.excess_spl_owed[slot]
+                .checked_sub(excess_tokens)
+                .ok_or(PresaleError::Overflow)?;
+        }
+
+        emit!(ExcessClaimed {
+            contributor: ctx.accounts.payer.key(),
+            lamports: excess_lamports,
+            spl_mint: spl_slot.map(|slot| config.accepted_spl_mints[slot]),
+            spl_amount: excess_tokens
+        });
+        Ok(())
+    }
+
+    pub fn set_tge(ctx: Context<SetTge>, tge_timestamp: i64) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
+        require!(
+            ctx.accounts.sale_state.finalized,
+            PresaleError::SaleNotFinalized
+        );
+        let cfg = &mut ctx.accounts.config;
+        require!(cfg.tge_timestamp == 0, PresaleError::TgeAlreadySet);
+        require!(
+            tge_timestamp > 0 && tge_timestamp >= Clock::get()?.unix_timestamp,
+            PresaleError::InvalidTgeTimestamp
+        );
+        cfg.tge_timestamp = tge_timestamp;
+        emit!(TgeSet { tge_timestamp });
+        Ok(())
+    }
+
+    pub fn set_whitelist_root(ctx: Context<AdminToggle>, whitelist_root: [u8; 32]) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
+        let cfg = &mut ctx.accounts.config;
+        cfg.whitelist_root = whitelist_root;
+        emit!(WhitelistRootUpdated { whitelist_root });
+        Ok(())
+    }
+
     pub fn pause(ctx: Context<AdminToggle>) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
         let cfg = &mut ctx.accounts.config;
         cfg.paused = true;
         emit!(PresalePaused {});
@@ -231,11 +926,56 @@ pub mod myxen_presale {
     }
 
     pub fn unpause(ctx: Context<AdminToggle>) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
         let cfg = &mut ctx.accounts.config;
         cfg.paused = false;
         emit!(PresaleUnpaused {});
         Ok(())
     }
+
+    /// First step of an ownership handover; the new owner (e.g. the owner set PDA) must accept
+    pub fn propose_admin(ctx: Context<AdminToggle>, new_owner: Pubkey) -> Result<()> {
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.owner,
+            &ctx.accounts.owner,
+            ctx.remaining_accounts,
+        )?;
+        let cfg = &mut ctx.accounts.config;
+        cfg.pending_owner = new_owner;
+        emit!(OwnerProposed {
+            owner: cfg.owner,
+            pending_owner: new_owner
+        });
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        require!(
+            ctx.accounts.config.pending_owner != Pubkey::default(),
+            PresaleError::NoPendingOwner
+        );
+        require_authority(
+            &ctx.accounts.config.key(),
+            &ctx.accounts.config.pending_owner,
+            &ctx.accounts.new_owner,
+            ctx.remaining_accounts,
+        )?;
+        let cfg = &mut ctx.accounts.config;
+        let previous = cfg.owner;
+        cfg.owner = cfg.pending_owner;
+        cfg.pending_owner = Pubkey::default();
+        emit!(OwnerChanged {
+            previous,
+            owner: cfg.owner
+        });
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -245,6 +985,13 @@ pub struct Attestation {
     pub signature: [u8; 64],
 }
 
+/// Merkle proof of a (buyer, max_allocation_usd) whitelist leaf
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WhitelistProof {
+    pub max_allocation_usd: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct InitializeParams {
     pub token_mint: Pubkey,
@@ -257,10 +1004,17 @@ pub struct InitializeParams {
     pub max_per_wallet_usd: u64,
     pub min_buy_usd: u64,
     pub accepted_spl_mints: [Pubkey; 4],
+    pub tge_unlock_bps: u16,
+    pub cliff_seconds: u64,
     pub daily_release_bps: u16,
     pub vesting_days: u16,
     pub fee_bps: u16,
     pub oracle_pubkey: Pubkey,
+    pub extension_window_secs: i64,
+    pub extension_secs: i64,
+    pub extension_min_usd: u64,
+    pub max_end_ts: i64,
+    pub oversubscription: bool,
 }
 
 #[derive(Accounts)]
@@ -275,62 +1029,228 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-pub struct Buy<'info> {
-    #[account(mut, has_one = treasury)]
+pub struct ConfigureRounds<'info> {
+    #[account(seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
     pub config: Account<'info, PresaleConfig>,
+    #[account(init_if_needed, payer = owner, space = 8 + SaleState::SIZE, seeds = [b"sale-state", config.key().as_ref()], bump)]
+    pub sale_state: Account<'info, SaleState>,
     #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    #[account(mut, seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
+    pub config: Account<'info, PresaleConfig>,
+    #[account(mut, seeds = [b"sale-state", config.key().as_ref()], bump)]
     pub sale_state: Account<'info, SaleState>,
-    #[account(init_if_needed, payer = payer, space = 8 + Contributor::SIZE, seeds = [b"contributor", payer.key.as_ref()], bump)]
+    #[account(init_if_needed, payer = payer, space = 8 + Contributor::SIZE, seeds = [b"contributor", config.key().as_ref(), payer.key.as_ref()], bump)]
     pub contributor: Account<'info, Contributor>,
     /// CHECK: oracle account - optional Pyth account or price feed
     pub oracle: UncheckedAccount<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
-    /// CHECK: treasury lamports account
-    #[account(mut)]
-    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: system-owned SOL escrow PDA, verified through seeds
+    #[account(mut, seeds = [b"sol-escrow", config.key().as_ref()], bump)]
+    pub sol_escrow: UncheckedAccount<'info>,
     /// SPL payment token account (if paying with SPL)
     #[account(mut)]
     pub payment_token_account: Option<Account<'info, TokenAccount>>,
-    /// treasury token account for SPL
+    /// SPL escrow: the config PDA's ATA for the payment mint (created with the ATA program)
     #[account(mut)]
-    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct Claim<'info> {
+    #[account(mut, seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
     pub config: Account<'info, PresaleConfig>,
-    #[account(mut, seeds = [b"contributor", payer.key.as_ref()], bump)]
+    #[account(seeds = [b"sale-state", config.key().as_ref()], bump)]
+    pub sale_state: Account<'info, SaleState>,
+    #[account(mut, seeds = [b"contributor", config.key().as_ref(), payer.key.as_ref()], bump)]
     pub contributor: Account<'info, Contributor>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    /// sale inventory: ATA of token_mint owned by the config PDA
+    #[account(mut, associated_token::mint = config.token_mint, associated_token::authority = config)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, associated_token::mint = config.token_mint, associated_token::authority = payer)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct FundVault<'info> {
+    #[account(has_one = token_mint)]
+    pub config: Account<'info, PresaleConfig>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, token::mint = token_mint, token::authority = owner)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    #[account(init_if_needed, payer = owner, associated_token::mint = token_mint, associated_token::authority = config)]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckSolvency<'info> {
+    pub config: Account<'info, PresaleConfig>,
+    #[account(seeds = [b"sale-state", config.key().as_ref()], bump)]
+    pub sale_state: Account<'info, SaleState>,
+    #[account(associated_token::mint = config.token_mint, associated_token::authority = config)]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureOwners<'info> {
+    #[account(seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
+    pub config: Account<'info, PresaleConfig>,
+    #[account(init_if_needed, payer = owner, space = 8 + OwnerSet::SIZE, seeds = [b"owners", config.key().as_ref()], bump)]
+    pub owner_set: Account<'info, OwnerSet>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOwnerSet<'info> {
+    #[account(seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
+    pub config: Account<'info, PresaleConfig>,
+    #[account(mut, seeds = [b"owners", config.key().as_ref()], bump = owner_set.bump)]
+    pub owner_set: Account<'info, OwnerSet>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeWithdrawal<'info> {
+    pub config: Account<'info, PresaleConfig>,
+    #[account(mut, seeds = [b"owners", config.key().as_ref()], bump = owner_set.bump)]
+    pub owner_set: Account<'info, OwnerSet>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + WithdrawalProposal::SIZE,
+        seeds = [b"withdrawal", config.key().as_ref(), &owner_set.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, WithdrawalProposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWithdrawal<'info> {
+    pub config: Account<'info, PresaleConfig>,
+    #[account(seeds = [b"owners", config.key().as_ref()], bump = owner_set.bump)]
+    pub owner_set: Account<'info, OwnerSet>,
+    #[account(mut, seeds = [b"withdrawal", config.key().as_ref(), &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, WithdrawalProposal>,
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
+    pub config: Account<'info, PresaleConfig>,
+    #[account(seeds = [b"owners", config.key().as_ref()], bump = owner_set.bump)]
+    pub owner_set: Account<'info, OwnerSet>,
+    #[account(mut, seeds = [b"withdrawal", config.key().as_ref(), &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, WithdrawalProposal>,
+    #[account(seeds = [b"sale-state", config.key().as_ref()], bump)]
+    pub sale_state: Account<'info, SaleState>,
+    pub executor: Signer<'info>,
+    /// CHECK: system-owned SOL escrow PDA that receives buy proceeds, verified through seeds
+    #[account(mut, seeds = [b"sol-escrow", config.key().as_ref()], bump)]
+    pub sol_escrow: UncheckedAccount<'info>,
+    /// SPL escrow for proposal.mint (SPL proposals)
+    #[account(mut)]
+    pub source_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: must equal proposal.destination
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawFunds<'info> {
-    #[account(mut, has_one = owner)]
+pub struct CancelWithdrawal<'info> {
     pub config: Account<'info, PresaleConfig>,
-    /// CHECK: multisig owner
+    #[account(seeds = [b"owners", config.key().as_ref()], bump = owner_set.bump)]
+    pub owner_set: Account<'info, OwnerSet>,
+    #[account(mut, close = proposer, seeds = [b"withdrawal", config.key().as_ref(), &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, WithdrawalProposal>,
+    /// CHECK: refunded the proposal rent; must be proposal.proposer
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
     pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSale<'info> {
+    pub config: Account<'info, PresaleConfig>,
+    #[account(mut, seeds = [b"sale-state", config.key().as_ref()], bump)]
+    pub sale_state: Account<'info, SaleState>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimExcess<'info> {
+    #[account(seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
+    pub config: Account<'info, PresaleConfig>,
+    #[account(mut, seeds = [b"sale-state", config.key().as_ref()], bump)]
+    pub sale_state: Account<'info, SaleState>,
+    #[account(mut, seeds = [b"contributor", config.key().as_ref(), payer.key.as_ref()], bump)]
+    pub contributor: Account<'info, Contributor>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: system-owned SOL escrow PDA, verified through seeds
+    #[account(mut, seeds = [b"sol-escrow", config.key().as_ref()], bump)]
+    pub sol_escrow: UncheckedAccount<'info>,
+    /// SPL escrow for the stablecoin being reclaimed
     #[account(mut)]
-    pub escrow: UncheckedAccount<'info>,
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
     #[account(mut)]
-    pub treasury: UncheckedAccount<'info>,
+    pub recipient_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTge<'info> {
+    #[account(mut, seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
+    pub config: Account<'info, PresaleConfig>,
+    #[account(seeds = [b"sale-state", config.key().as_ref()], bump)]
+    pub sale_state: Account<'info, SaleState>,
+    pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct AdminToggle<'info> {
-    #[account(mut, has_one = owner)]
+    #[account(mut, seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
     pub config: Account<'info, PresaleConfig>,
     pub owner: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [b"presale-config", config.token_mint.as_ref()], bump)]
+    pub config: Account<'info, PresaleConfig>,
+    /// the pending owner, or one of its owners when the pending owner is the owner set PDA
+    pub new_owner: Signer<'info>,
+}
+
 #[account]
+#[derive(Default)]
 pub struct PresaleConfig {
     pub owner: Pubkey,
+    pub pending_owner: Pubkey,
     pub token_mint: Pubkey,
     pub treasury: Pubkey,
     pub total_presale_tokens: u64,
@@ -347,22 +1267,168 @@ pub struct PresaleConfig {
     pub vesting_days: u16,
     pub fee_bps: u16,
     pub paused: bool,
+    pub whitelist_root: [u8; 32],
+    pub total_claimed: u64,
+    pub tge_timestamp: i64,
+    pub tge_unlock_bps: u16,
+    pub cliff_seconds: u64,
+    pub extension_window_secs: i64, // a big buy this close to the end extends the final round
+    pub extension_secs: i64,        // 0 = extensions disabled
+    pub extension_min_usd: u64,
+    pub max_end_ts: i64,        // hard ceiling for extensions
+    pub oversubscription: bool, // fair launch: accept buys past the cap, pro-rate at finalize
 }
 
 impl PresaleConfig {
-    // rough size: 3 pubkeys (owner, token_mint, treasury) = 96
-    // plus u64 fields and extras; include 4 additional pubkeys for accepted_spl_mints
-    pub const SIZE: usize = 96 + 8 * 7 + 128 + 1 + 2 + 2 + 2 + 1; // approximate
+    pub fn accepts_spl_mint(&self, mint: &Pubkey) -> bool {
+        self.spl_mint_slot(mint).is_some()
+    }
+
+    pub fn spl_mint_slot(&self, mint: &Pubkey) -> Option<usize> {
+        self.accepted_spl_mints[..self.accepted_spl_mint_count as usize]
+            .iter()
+            .position(|m| m == mint)
+    }
+
+    // field by field, in declaration order
+    pub const SIZE: usize = 32 * 4 // owner, pending_owner, token_mint, treasury
+        + 8 * 7 // allocations, prices and wallet limits
+        + 32 * 4 + 1 // accepted_spl_mints and their count
+        + 32 // oracle_pubkey
+        + 2 * 3 + 1 // daily_release_bps, vesting_days, fee_bps, paused
+        + 32 // whitelist_root
+        + 8 + 8 + 2 + 8 // total_claimed and the TGE vesting fields
+        + 8 * 4 // sale extension settings and max_end_ts
+        + 1; // oversubscription
+}
+
+/// M-of-N owners that approve treasury withdrawals, with an optional timelock
+#[account]
+pub struct OwnerSet {
+    pub config: Pubkey,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timelock_seconds: i64,
+    pub proposal_count: u64,
+    pub bump: u8,
+}
+
+impl OwnerSet {
+    pub const SIZE: usize = 32 + 4 + 32 * MAX_OWNERS + 1 + 8 + 8 + 1;
+
+    pub fn is_valid(owners: &[Pubkey], threshold: u8, timelock_seconds: i64) -> bool {
+        !owners.is_empty()
+            && owners.len() <= MAX_OWNERS
+            && threshold > 0
+            && threshold as usize <= owners.len()
+            && timelock_seconds >= 0
+            && owners
+                .iter()
+                .enumerate()
+                .all(|(i, o)| !owners[..i].contains(o))
+    }
+
+    /// Whether `signer` and the signing accounts in `cosigners` reach the threshold
+    pub fn is_approved(&self, signer: &AccountInfo, cosigners: &[AccountInfo]) -> bool {
+        let mut signers = vec![*signer.key];
+        signers.extend(cosigners.iter().filter(|i| i.is_signer).map(|i| *i.key));
+        let approvals = self.owners.iter().filter(|o| signers.contains(o)).count();
+        self.threshold > 0 && approvals >= self.threshold as usize
+    }
+}
+
+#[account]
+pub struct WithdrawalProposal {
+    pub config: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub currency: Currency,
+    pub mint: Pubkey, // SPL proposals only
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub approvals: Vec<Pubkey>,
+    pub eta: i64, // earliest execution time
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl WithdrawalProposal {
+    pub const SIZE: usize = 32 + 8 + 32 + 1 + 32 + 8 + 32 + 4 + 32 * MAX_OWNERS + 8 + 1 + 1;
 }
 
 #[account]
 pub struct SaleState {
     pub sold_tokens: u64,
-    pub phase: u8, // 0 private, 1 public
+    pub phase: u8, // index of the current round
+    pub round_count: u8,
+    pub rounds: [SaleRound; MAX_ROUNDS],
+    pub finalized: bool,
+    pub total_requested: u64, // tokens bid in an oversubscribed sale, set at finalize
+    pub raised_lamports: u64,
+    pub raised_spl: [u64; 4],      // indexed like config.accepted_spl_mints
+    pub excess_lamports_owed: u64, // unclaimed claim_excess amounts, set at finalize
+    pub excess_spl_owed: [u64; 4],
 }
 
 impl SaleState {
-    pub const SIZE: usize = 8 + 1;
+    pub const SIZE: usize =
+        8 + 1 + 1 + SaleRound::SIZE * MAX_ROUNDS + 1 + 8 * 2 + 8 * 4 + 8 + 8 * 4;
+
+    pub fn is_oversubscribed(&self) -> bool {
+        self.total_requested > self.sold_tokens
+    }
+
+    /// share of a requested allocation kept after pro-rating to the cap
+    pub fn filled(&self, amount: u64) -> u64 {
+        if !self.is_oversubscribed() {
+            return amount;
+        }
+        ((amount as u128 * self.sold_tokens as u128) / self.total_requested as u128) as u64
+    }
+
+    /// share of a deposit returned by claim_excess; rounds down so it never exceeds the reserve
+    pub fn excess(&self, amount: u64) -> u64 {
+        if !self.is_oversubscribed() {
+            return 0;
+        }
+        ((amount as u128 * (self.total_requested - self.sold_tokens) as u128)
+            / self.total_requested as u128) as u64
+    }
+
+    /// end of the final round, including any extensions
+    pub fn end_ts(&self) -> i64 {
+        self.rounds[..self.round_count as usize]
+            .last()
+            .map_or(0, |r| r.end_ts)
+    }
+
+    pub fn active_round(&self, now: i64) -> Option<usize> {
+        self.rounds[..self.round_count as usize]
+            .iter()
+            .position(|r| now >= r.start_ts && now < r.end_ts)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SaleRound {
+    pub kind: RoundKind,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub price_usd_micro: u64,
+    pub token_cap: u64,
+    pub wallet_cap_usd: u64,
+    pub sold_tokens: u64,
+}
+
+impl SaleRound {
+    pub const SIZE: usize = 1 + 8 * 6;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundKind {
+    #[default]
+    Private,
+    Public,
 }
 
 #[account]
@@ -371,13 +1437,20 @@ pub struct Contributor {
     pub contribution_usd: u64,
     pub claimable_tokens: u64,
     pub claimed_tokens: u64,
+    // deprecated: vesting runs from config.tge_timestamp with the config schedule;
+    // these three are no longer written or read and only keep the account layout
     pub vesting_start_ts: u64,
     pub daily_release_bps: u16,
     pub vesting_days: u16,
+    pub round_contributions_usd: [u64; MAX_ROUNDS],
+    pub contribution_lamports: u64,
+    pub contribution_spl: [u64; 4], // indexed like config.accepted_spl_mints
+    pub excess_sol_claimed: bool,
+    pub excess_spl_claimed: [bool; 4],
 }
 
 impl Contributor {
-    pub const SIZE: usize = 8 * 5 + 2 + 2;
+    pub const SIZE: usize = 8 * 5 + 2 + 2 + 8 * MAX_ROUNDS + 8 + 8 * 4 + 1 + 4;
 }
 
 #[event]
@@ -389,6 +1462,30 @@ pub struct ContributionRecorded {
     pub tx_hash: [u8; 32],
 }
 
+#[event]
+pub struct RoundsConfigured {
+    pub round_count: u8,
+}
+
+#[event]
+pub struct PhaseAdvanced {
+    pub from: u8,
+    pub to: u8,
+}
+
+#[event]
+pub struct VaultFunded {
+    pub amount: u64,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct SolvencyChecked {
+    pub vault_balance: u64,
+    pub outstanding: u64,
+    pub solvent: bool,
+}
+
 #[event]
 pub struct FundsTransferredToTreasury {
     pub amount: u64,
@@ -403,6 +1500,94 @@ pub struct VestingClaimed {
     pub timestamp: u64,
 }
 
+#[event]
+pub struct TgeSet {
+    pub tge_timestamp: i64,
+}
+
+#[event]
+pub struct WhitelistRootUpdated {
+    pub whitelist_root: [u8; 32],
+}
+
+#[event]
+pub struct OwnerProposed {
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+#[event]
+pub struct OwnerChanged {
+    pub previous: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct OwnersConfigured {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timelock_seconds: i64,
+}
+
+#[event]
+pub struct WithdrawalProposed {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub currency: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct WithdrawalApproved {
+    pub id: u64,
+    pub approver: Pubkey,
+    pub approvals: u8,
+}
+
+#[event]
+pub struct WithdrawalCancelled {
+    pub id: u64,
+    pub canceller: Pubkey,
+}
+
+#[event]
+pub struct WithdrawalExecuted {
+    pub id: u64,
+    pub executor: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+}
+
+#[event]
+pub struct SaleExtended {
+    pub contributor: Pubkey,
+    pub usd_value: u64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct SaleOversubscribed {
+    pub total_requested: u64,
+    pub sold_tokens: u64,
+}
+
+#[event]
+pub struct ExcessClaimed {
+    pub contributor: Pubkey,
+    pub lamports: u64,
+    pub spl_mint: Option<Pubkey>,
+    pub spl_amount: u64,
+}
+
+#[event]
+pub struct SaleFinalized {
+    pub sold_tokens: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct PresalePaused {}
 
@@ -415,6 +1600,36 @@ pub enum Currency {
     SPL,
 }
 
+/// Require `signer` to be `authority`, or `authority` to be this config's owner set
+/// approved by `signer` plus co-signers
+///
+/// In the owner set case the owner set account is the first of `remaining`
+/// and the co-signing owners follow it.
+fn require_authority(
+    config: &Pubkey,
+    authority: &Pubkey,
+    signer: &AccountInfo,
+    remaining: &[AccountInfo],
+) -> Result<()> {
+    if signer.key == authority {
+        return Ok(());
+    }
+    let (owner_set_info, cosigners) = remaining
+        .split_first()
+        .ok_or(error!(PresaleError::NotAnOwner))?;
+    require!(
+        owner_set_info.key == authority && owner_set_info.owner == &crate::ID,
+        PresaleError::NotAnOwner
+    );
+    let owner_set = OwnerSet::try_deserialize(&mut &owner_set_info.try_borrow_data()?[..])?;
+    require!(owner_set.config == *config, PresaleError::NotAnOwner);
+    require!(
+        owner_set.is_approved(signer, cosigners),
+        PresaleError::ThresholdNotMet
+    );
+    Ok(())
+}
+
 fn verify_attestation(
     attestation: &Attestation,
     payer: &Pubkey,
@@ -437,6 +1652,52 @@ fn verify_attestation(
     Ok(attestation.usd_value)
 }
 
+// tge_unlock_bps unlocks at TGE; the rest releases daily_release_bps per day after the cliff
+fn tge_vested_amount(
+    total: u64,
+    tge_ts: i64,
+    now: i64,
+    tge_unlock_bps: u16,
+    cliff_seconds: u64,
+    daily_release_bps: u16,
+    vesting_days: u64,
+) -> u64 {
+    if now < tge_ts {
+        return 0;
+    }
+    let tge_amount = ((total as u128 * tge_unlock_bps as u128) / 10000u128) as u64;
+    let cliff_end = tge_ts.saturating_add(cliff_seconds as i64);
+    if now < cliff_end {
+        return tge_amount;
+    }
+    let days = ((now - cliff_end) as u64) / 86400u64;
+    if days >= vesting_days {
+        return total;
+    }
+    let remaining = (total - tge_amount) as u128;
+    let linear = (remaining * daily_release_bps as u128 * days as u128) / 10000u128;
+    tge_amount + linear.min(remaining) as u64
+}
+
+// sorted-pair keccak Merkle proof; leaves prefixed 0x00, inner nodes 0x01
+fn verify_whitelist_proof(
+    root: &[u8; 32],
+    buyer: &Pubkey,
+    max_allocation_usd: u64,
+    proof: &[[u8; 32]],
+) -> bool {
+    let mut node = keccak::hashv(&[&[0u8], buyer.as_ref(), &max_allocation_usd.to_le_bytes()]).0;
+    for sibling in proof {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        node = keccak::hashv(&[&[1u8], &left, &right]).0;
+    }
+    node == *root
+}
+
 fn get_usd_value_from_pyth(lamports: u64, pyth_account: &UncheckedAccount) -> Result<u64> {
     // Parse Pyth price account
     let data = pyth_account.to_account_info().data.borrow();
@@ -496,4 +1757,73 @@ pub enum PresaleError {
     NothingToClaim,
     #[msg("No vesting started")]
     NoVesting,
+    #[msg("Invalid sale rounds")]
+    InvalidRounds,
+    #[msg("Round allocation exceeds configured allocation")]
+    AllocationExceeded,
+    #[msg("Sale already started")]
+    SaleAlreadyStarted,
+    #[msg("No sale round is active")]
+    RoundNotActive,
+    #[msg("Whitelist proof required")]
+    WhitelistProofRequired,
+    #[msg("Invalid whitelist proof")]
+    InvalidWhitelistProof,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Vault balance does not cover outstanding tokens")]
+    VaultInsolvent,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("TGE already set")]
+    TgeAlreadySet,
+    #[msg("Invalid TGE timestamp")]
+    InvalidTgeTimestamp,
+    #[msg("No ownership handover pending")]
+    NoPendingOwner,
+    #[msg("Signer is not the pending owner")]
+    NotPendingOwner,
+    #[msg("Owner set already configured")]
+    OwnersAlreadyConfigured,
+    #[msg("Invalid owner set or threshold")]
+    InvalidOwnerSet,
+    #[msg("Signer is not an owner")]
+    NotAnOwner,
+    #[msg("Proposal already executed")]
+    ProposalExecuted,
+    #[msg("Owner already approved")]
+    AlreadyApproved,
+    #[msg("Approval threshold not met")]
+    ThresholdNotMet,
+    #[msg("Timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Required account missing")]
+    MissingAccount,
+    #[msg("Destination does not match proposal")]
+    InvalidDestination,
+    #[msg("Sale already finalized")]
+    SaleFinalized,
+    #[msg("Sale is still open")]
+    SaleStillOpen,
+    #[msg("Sale not finalized")]
+    SaleNotFinalized,
+    #[msg("Escrow account does not match")]
+    InvalidEscrow,
+    #[msg("Sale was not oversubscribed")]
+    NotOversubscribed,
+    #[msg("Excess already claimed")]
+    ExcessAlreadyClaimed,
+    #[msg("Amount would spend excess owed to contributors")]
+    ExcessReserved,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presale_config_size_covers_serialized_account() {
+        let serialized = PresaleConfig::default().try_to_vec().unwrap().len();
+        assert!(8 + PresaleConfig::SIZE >= serialized + 8);
+    }
 }
@@ -0,0 +1,242 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+
+declare_id!("FysNFYtV4uumYDx75dFmYnejMPMNAEtfz2ujsTrjSUZd");
+
+/// Mock constant-product AMM for localnet testing
+///
+/// Implements the `add_liquidity` interface that `myxn_presale::create_liquidity`
+/// calls, so LP creation can be exercised without a live Raydium/Orca pool.
+/// Not intended for deployment.
+#[program]
+pub mod mock_amm {
+    use super::*;
+
+    /// Create a pool for the (mint_a, mint_b) pair with program-owned vaults and LP mint
+    pub fn initialize_pool(ctx: Context<InitializePool>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+
+        pool.mint_a = ctx.accounts.mint_a.key();
+        pool.mint_b = ctx.accounts.mint_b.key();
+        pool.vault_a = ctx.accounts.vault_a.key();
+        pool.vault_b = ctx.accounts.vault_b.key();
+        pool.lp_mint = ctx.accounts.lp_mint.key();
+        pool.bump = ctx.bumps.pool;
+
+        msg!("Pool initialized for {} / {}", pool.mint_a, pool.mint_b);
+
+        Ok(())
+    }
+
+    /// Deposit both sides and mint LP tokens to the depositor
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+        min_lp_out: u64,
+    ) -> Result<()> {
+        require!(amount_a > 0 && amount_b > 0, AmmError::ZeroAmount);
+
+        let reserve_a = ctx.accounts.vault_a.amount;
+        let reserve_b = ctx.accounts.vault_b.amount;
+        let supply = ctx.accounts.lp_mint.supply;
+
+        let lp_out = if supply == 0 {
+            isqrt((amount_a as u128) * (amount_b as u128))
+        } else {
+            let from_a = (amount_a as u128) * (supply as u128) / (reserve_a as u128);
+            let from_b = (amount_b as u128) * (supply as u128) / (reserve_b as u128);
+            from_a.min(from_b)
+        };
+        let lp_out = u64::try_from(lp_out).map_err(|_| error!(AmmError::MathOverflow))?;
+        require!(lp_out >= min_lp_out, AmmError::SlippageExceeded);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_a.to_account_info(),
+                    to: ctx.accounts.vault_a.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount_a,
+        )?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_b.to_account_info(),
+                    to: ctx.accounts.vault_b.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount_b,
+        )?;
+
+        let pool = &ctx.accounts.pool;
+        let seeds = &[
+            b"pool".as_ref(),
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            &[pool.bump],
+        ];
+        let signer = &[&seeds[..]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.depositor_lp.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer,
+            ),
+            lp_out,
+        )?;
+
+        msg!(
+            "Added liquidity: {} A + {} B for {} LP",
+            amount_a,
+            amount_b,
+            lp_out
+        );
+
+        Ok(())
+    }
+}
+
+fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool", mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub mint_a: Account<'info, Mint>,
+    pub mint_b: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"vault_a", pool.key().as_ref()],
+        bump,
+        token::mint = mint_a,
+        token::authority = pool
+    )]
+    pub vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"vault_b", pool.key().as_ref()],
+        bump,
+        token::mint = mint_b,
+        token::authority = pool
+    )]
+    pub vault_b: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"lp_mint", pool.key().as_ref()],
+        bump,
+        mint::decimals = 9,
+        mint::authority = pool
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account order is part of the CPI interface used by `myxn_presale`
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(
+        seeds = [b"pool", pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.bump,
+        has_one = vault_a,
+        has_one = vault_b,
+        has_one = lp_mint
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub depositor: Signer<'info>,
+
+    #[account(mut, token::mint = pool.mint_a)]
+    pub depositor_token_a: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = pool.mint_b)]
+    pub depositor_token_b: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub vault_a: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub vault_b: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = lp_mint)]
+    pub depositor_lp: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// ============================================================================
+// State
+// ============================================================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub bump: u8,
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[error_code]
+pub enum AmmError {
+    #[msg("Deposit amounts must be non-zero")]
+    ZeroAmount,
+
+    #[msg("LP output below minimum")]
+    SlippageExceeded,
+
+    #[msg("Math overflow")]
+    MathOverflow,
+}
@@ -1,5 +1,38 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::{hash, keccak};
+use anchor_spl::associated_token::{self, get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, spl_token, Mint, SyncNative, Token, TokenAccount, Transfer};
+use pyth_sdk_solana::{load_price_feed_from_account_info, Price};
+
+/// Basis-point denominator used for fee splits and vesting rates
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// USD amounts are denominated in micro-USD (6 decimals)
+pub const USD_SCALE: u64 = 1_000_000;
+
+/// Maximum number of SPL stablecoins accepted as payment
+pub const MAX_SPL_MINTS: usize = 4;
+
+/// Lamports per SOL
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Maximum number of milestones in a custom vesting schedule
+pub const MAX_MILESTONES: usize = 16;
+
+/// Maximum number of members in an admin multisig
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+
+/// Minimum time a queued parameter change stays visible before it can apply
+pub const PARAMS_UPDATE_DELAY_SECS: i64 = 48 * 60 * 60;
+
+/// `PresaleState::paused` bits, each halting one class of instruction
+pub const PAUSE_PURCHASES: u8 = 1 << 0;
+pub const PAUSE_CLAIMS: u8 = 1 << 1;
+pub const PAUSE_REFUNDS: u8 = 1 << 2;
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 3; // fee distribution, LP creation, unlock and revocation
+pub const PAUSE_ALL: u8 = PAUSE_PURCHASES | PAUSE_CLAIMS | PAUSE_REFUNDS | PAUSE_WITHDRAWALS;
 
 declare_id!("Frj9BHHhTVL36asW7KoBpJs17eEt4BUvL6fV5kc8xXd7");
 
@@ -8,9 +41,16 @@ declare_id!("Frj9BHHhTVL36asW7KoBpJs17eEt4BUvL6fV5kc8xXd7");
 /// Features:
 /// - Presale purchase tracking and escrow
 /// - Linear vesting with configurable schedule (5%/day default)
+/// - Team/advisor/marketing/ecosystem allocation pools with vesting grants
 /// - Conditional LP creation based on raise thresholds
 /// - Fee routing (burn, charity, liquidity, treasury)
 /// - Admin controls for finalization and refunds
+/// - Guardian-controlled pause of purchases, claims, refunds and withdrawals
+/// - Two-step admin handover, optionally to an M-of-N multisig
+///
+/// Admin instructions accept the admin key itself or, when the admin is a
+/// `Multisig` account, any member as `admin` with the multisig account as the
+/// first remaining account and further co-signing members after it.
 #[program]
 pub mod myxn_presale {
     use super::*;
@@ -23,22 +63,92 @@ pub mod myxn_presale {
         let presale = &mut ctx.accounts.presale_state;
 
         presale.admin = ctx.accounts.admin.key();
+        presale.pending_admin = Pubkey::default();
         presale.token_mint = ctx.accounts.token_mint.key();
         presale.treasury = ctx.accounts.treasury.key();
         presale.burn_wallet = params.burn_wallet;
         presale.charity_wallet = params.charity_wallet;
+        presale.liquidity_wallet = params.liquidity_wallet;
+
+        require!(params.fee_split.is_valid(), PresaleError::InvalidFeeSplit);
+        presale.fee_split = params.fee_split;
+        // The sale token can never double as a payment mint, so its vault is never an escrow
+        require!(
+            !params.accepted_spl_mints.contains(&presale.token_mint),
+            PresaleError::UnsupportedSplMint
+        );
+        presale.accepted_spl_mints = params.accepted_spl_mints;
+
+        require!(
+            params.vesting_schedule.is_valid(),
+            PresaleError::InvalidVestingSchedule
+        );
+        presale.vesting_schedule = params.vesting_schedule;
+        presale.vesting_transfer_requires_admin = params.vesting_transfer_requires_admin;
+        presale.guardian = params.guardian;
+        presale.paused = 0;
+        presale.pending_params = None;
+        presale.pending_params_eta = 0;
+
+        require!(
+            params.max_slippage_bps as u64 <= BPS_DENOMINATOR
+                && params.oracle_max_conf_bps as u64 <= BPS_DENOMINATOR,
+            PresaleError::InvalidOracleConfig
+        );
+        presale.sol_price_feed = params.sol_price_feed;
+        presale.oracle_max_age_secs = params.oracle_max_age_secs;
+        presale.oracle_max_conf_bps = params.oracle_max_conf_bps;
+        presale.max_slippage_bps = params.max_slippage_bps;
+
+        require!(
+            params.lp_share_bps as u64 <= BPS_DENOMINATOR && params.listing_price_usd > 0,
+            PresaleError::InvalidLpConfig
+        );
+        presale.lp_share_bps = params.lp_share_bps;
+        presale.listing_price_usd = params.listing_price_usd;
+        presale.amm_program = params.amm_program;
+
+        require!(
+            params.lp_lock_seconds >= 0 && params.lp_release_seconds >= 0,
+            PresaleError::InvalidLpConfig
+        );
+        presale.lp_lock_seconds = params.lp_lock_seconds;
+        presale.lp_release_seconds = params.lp_release_seconds;
 
         presale.presale_tokens = params.presale_tokens;
+        presale.oversubscription = params.oversubscription;
         presale.price_usd = params.price_usd;
         presale.max_per_wallet_usd = params.max_per_wallet_usd;
         presale.lp_target_usd = params.lp_target_usd;
         presale.lp_min_threshold_usd = params.lp_min_threshold_usd;
 
+        require!(
+            params.start_ts < params.end_ts
+                && params.end_ts <= params.max_end_ts
+                && params.extension_window_secs >= 0
+                && params.extension_secs >= 0,
+            PresaleError::InvalidSaleWindow
+        );
+        presale.start_ts = params.start_ts;
+        presale.end_ts = params.end_ts;
+        presale.max_end_ts = params.max_end_ts;
+        presale.extension_window_secs = params.extension_window_secs;
+        presale.extension_secs = params.extension_secs;
+        presale.extension_min_usd = params.extension_min_usd;
+
         presale.total_raised_usd = 0;
+        presale.total_raised_lamports = 0;
+        presale.total_raised_spl = [0; MAX_SPL_MINTS];
+        presale.excess_lamports_owed = 0;
+        presale.excess_spl_owed = [0; MAX_SPL_MINTS];
         presale.total_sold = 0;
+        presale.total_requested = 0;
+        presale.total_granted = 0;
+        presale.total_claimed = 0;
         presale.is_active = true;
         presale.is_finalized = false;
         presale.refund_enabled = false;
+        presale.whitelist_only = false;
         presale.bump = ctx.bumps.presale_state;
 
         msg!(
@@ -51,73 +161,228 @@ pub mod myxn_presale {
     }
 
     /// Purchase tokens during presale
+    ///
+    /// The SOL leg is valued from the Pyth SOL/USD feed; `expected_usd` is the
+    /// buyer's quote for it and must be within `max_slippage_bps` of the oracle
+    /// value. The optional SPL leg must be an accepted stablecoin and is valued
+    /// 1:1 against USD.
+    ///
+    /// While `whitelist_only` is set, the buyer must prove a (buyer, max
+    /// allocation) leaf of the whitelist Merkle tree, and that allocation
+    /// replaces `max_per_wallet_usd` as the buyer's cap.
     pub fn purchase_presale(
         ctx: Context<PurchasePresale>,
-        amount_usd: u64,
+        expected_usd: u64,
         sol_amount: u64,
+        spl_amount: u64,
+        whitelist: Option<WhitelistProof>,
     ) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_PURCHASES)?;
         let presale = &mut ctx.accounts.presale_state;
         let purchase = &mut ctx.accounts.purchase_record;
 
         require!(presale.is_active, PresaleError::PresaleNotActive);
         require!(!presale.is_finalized, PresaleError::PresaleFinalized);
 
-        // Check max per wallet
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= presale.start_ts, PresaleError::SaleNotStarted);
+        require!(now < presale.end_ts, PresaleError::SaleEnded);
+
+        // Value the SOL leg from the oracle rather than trusting the client
+        let amount_usd = if sol_amount > 0 {
+            let price = load_sol_price(
+                &ctx.accounts.sol_price_feed,
+                presale.oracle_max_age_secs,
+                presale.oracle_max_conf_bps,
+            )?;
+            let oracle_usd = lamports_to_usd(sol_amount, &price)?;
+            require!(
+                within_slippage(oracle_usd, expected_usd, presale.max_slippage_bps),
+                PresaleError::SlippageExceeded
+            );
+            oracle_usd
+        } else {
+            0
+        };
+
+        // Resolve the SPL leg, if any, to its accepted mint slot and USD value
+        let spl_leg = if spl_amount > 0 {
+            let (Some(payment_mint), Some(_), Some(_)) = (
+                ctx.accounts.payment_mint.as_ref(),
+                ctx.accounts.buyer_token_account.as_ref(),
+                ctx.accounts.escrow_token_account.as_ref(),
+            ) else {
+                return err!(PresaleError::PaymentAccountMissing);
+            };
+            let slot = presale
+                .spl_mint_slot(&payment_mint.key())
+                .ok_or(PresaleError::UnsupportedSplMint)?;
+            let spl_usd = (spl_amount as u128)
+                .checked_mul(USD_SCALE as u128)
+                .ok_or(PresaleError::MathOverflow)?
+                / 10u128.pow(payment_mint.decimals as u32);
+            Some((slot, spl_usd as u64))
+        } else {
+            None
+        };
+        let spl_usd = spl_leg.map_or(0, |(_, usd)| usd);
+
+        let purchase_usd = amount_usd
+            .checked_add(spl_usd)
+            .ok_or(PresaleError::MathOverflow)?;
+
+        // Whitelisted buyers are capped by their proven allocation instead
+        let wallet_cap_usd = if presale.whitelist_only {
+            let proof = whitelist.ok_or(PresaleError::WhitelistProofRequired)?;
+            require!(
+                verify_whitelist_proof(
+                    &presale.whitelist_root,
+                    &ctx.accounts.buyer.key(),
+                    proof.max_allocation_usd,
+                    &proof.proof,
+                ),
+                PresaleError::InvalidWhitelistProof
+            );
+            proof.max_allocation_usd
+        } else {
+            presale.max_per_wallet_usd
+        };
+
+        // Check max per wallet across all of this buyer's purchases
         let current_purchases = purchase.total_purchased_usd;
         require!(
-            current_purchases + amount_usd <= presale.max_per_wallet_usd,
+            current_purchases
+                .checked_add(purchase_usd)
+                .ok_or(PresaleError::MathOverflow)?
+                <= wallet_cap_usd,
             PresaleError::MaxPurchaseExceeded
         );
 
         // Calculate tokens (amount in USD / price per token)
-        let tokens_to_buy = amount_usd
+        let tokens_to_buy = purchase_usd
             .checked_div(presale.price_usd)
             .ok_or(PresaleError::MathOverflow)?
             .checked_mul(1_000_000_000) // 9 decimals
             .ok_or(PresaleError::MathOverflow)?;
 
-        // Check availability
+        // Check availability; an oversubscribed sale is pro-rated at finalize
+        let total_sold = presale
+            .total_sold
+            .checked_add(tokens_to_buy)
+            .ok_or(PresaleError::MathOverflow)?;
         require!(
-            presale.total_sold + tokens_to_buy <= presale.presale_tokens,
+            presale.oversubscription || total_sold <= presale.presale_tokens,
             PresaleError::InsufficientPresaleTokens
         );
 
         // Transfer SOL to escrow
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            anchor_lang::system_program::Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: ctx.accounts.escrow_account.to_account_info(),
-            },
-        );
-        anchor_lang::system_program::transfer(transfer_ctx, sol_amount)?;
+        if sol_amount > 0 {
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.escrow_account.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(transfer_ctx, sol_amount)?;
+        }
+
+        // Transfer SPL stablecoin to the presale-owned escrow token account
+        if let (Some((slot, _)), Some(buyer_token_account), Some(escrow_token_account)) = (
+            spl_leg,
+            ctx.accounts.buyer_token_account.as_ref(),
+            ctx.accounts.escrow_token_account.as_ref(),
+        ) {
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: buyer_token_account.to_account_info(),
+                    to: escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            );
+            token::transfer(transfer_ctx, spl_amount)?;
+
+            purchase.spl_deposits[slot] = purchase.spl_deposits[slot]
+                .checked_add(spl_amount)
+                .ok_or(PresaleError::MathOverflow)?;
+            presale.total_raised_spl[slot] = presale.total_raised_spl[slot]
+                .checked_add(spl_amount)
+                .ok_or(PresaleError::MathOverflow)?;
+        }
 
         // Update records using checked arithmetic
         presale.total_raised_usd = presale
             .total_raised_usd
-            .checked_add(amount_usd)
+            .checked_add(purchase_usd)
             .ok_or(PresaleError::MathOverflow)?;
-        presale.total_sold = presale
-            .total_sold
-            .checked_add(tokens_to_buy)
+        presale.total_raised_lamports = presale
+            .total_raised_lamports
+            .checked_add(sol_amount)
             .ok_or(PresaleError::MathOverflow)?;
+        presale.total_sold = total_sold;
 
         purchase.buyer = ctx.accounts.buyer.key();
         purchase.total_purchased_usd = purchase
             .total_purchased_usd
-            .checked_add(amount_usd)
+            .checked_add(purchase_usd)
             .ok_or(PresaleError::MathOverflow)?;
         purchase.total_tokens = purchase
             .total_tokens
             .checked_add(tokens_to_buy)
             .ok_or(PresaleError::MathOverflow)?;
-        purchase.claimed_tokens = 0;
-        purchase.last_claim_timestamp = 0;
+        purchase.total_lamports = purchase
+            .total_lamports
+            .checked_add(sol_amount)
+            .ok_or(PresaleError::MathOverflow)?;
+
+        // A large buy close to the end pushes the end out, so late whales
+        // cannot close the sale before others can respond; never past max_end_ts
+        if presale.extension_secs > 0
+            && purchase_usd >= presale.extension_min_usd
+            && presale.end_ts - now <= presale.extension_window_secs
+            && presale.end_ts < presale.max_end_ts
+        {
+            presale.end_ts = presale
+                .end_ts
+                .checked_add(presale.extension_secs)
+                .ok_or(PresaleError::MathOverflow)?
+                .min(presale.max_end_ts);
+
+            emit!(SaleExtended {
+                buyer: ctx.accounts.buyer.key(),
+                purchase_usd,
+                end_ts: presale.end_ts,
+            });
+        }
+
+        // Purchase history; claim state is left untouched on repeat purchases
+        if purchase.purchase_count == 0 {
+            purchase.first_purchase_timestamp = now;
+        }
+        purchase.last_purchase_timestamp = now;
+        purchase.purchase_count = purchase
+            .purchase_count
+            .checked_add(1)
+            .ok_or(PresaleError::MathOverflow)?;
+
+        emit!(PurchaseRecorded {
+            buyer: purchase.buyer,
+            purchase_index: purchase.purchase_count,
+            amount_usd: purchase_usd,
+            sol_amount,
+            spl_amount,
+            tokens: tokens_to_buy,
+            total_purchased_usd: purchase.total_purchased_usd,
+            timestamp: now,
+        });
 
         msg!(
             "Purchase recorded: {} MYXN for ${}",
             tokens_to_buy / 1_000_000_000,
-            amount_usd
+            purchase_usd / USD_SCALE
         );
 
         Ok(())
@@ -128,70 +393,74 @@ pub mod myxn_presale {
         ctx: Context<InitializeVesting>,
         vesting_params: VestingParams,
     ) -> Result<()> {
-        let presale = &ctx.accounts.presale_state;
+        let presale = &mut ctx.accounts.presale_state;
         let vesting = &mut ctx.accounts.vesting_account;
 
         // Verify admin authorization
-        require!(
-            ctx.accounts.admin.key() == presale.admin,
-            PresaleError::Unauthorized
-        );
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+
+        // Presale grants are paid from the sale vault alongside purchases
+        presale.total_granted = presale
+            .total_granted
+            .checked_add(vesting_params.total_amount)
+            .ok_or(PresaleError::MathOverflow)?;
 
         vesting.beneficiary = ctx.accounts.beneficiary.key();
+        vesting.pool = Pubkey::default();
+        vesting.index = 0;
         vesting.total_amount = vesting_params.total_amount;
         vesting.start_timestamp = vesting_params.start_timestamp;
-        vesting.cliff_seconds = vesting_params.cliff_seconds;
-        vesting.daily_release_bps = vesting_params.daily_release_bps; // e.g., 500 = 5%
-        vesting.total_days = vesting_params.total_days;
+
+        require!(
+            vesting_params.schedule.is_valid(),
+            PresaleError::InvalidVestingSchedule
+        );
+        vesting.schedule = vesting_params.schedule;
         vesting.claimed_amount = 0;
+        vesting.revocable = false; // presale grants can never be clawed back
+        vesting.revoked = false;
+        vesting.claim_delegate = Pubkey::default();
+        vesting.approved_token_account = Pubkey::default();
         vesting.bump = ctx.bumps.vesting_account;
 
         msg!(
-            "Vesting initialized: {} tokens over {} days at {}% per day",
+            "Vesting initialized: {} tokens from {}",
             vesting_params.total_amount,
-            vesting_params.total_days,
-            vesting_params.daily_release_bps / 100
+            vesting_params.start_timestamp
         );
 
         Ok(())
     }
 
     /// Claim vested tokens
+    ///
+    /// Pass `vesting_account` to claim an admin-created grant, or omit it to
+    /// claim purchased tokens, which vest per the presale-wide schedule.
     pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
-        let vesting = &mut ctx.accounts.vesting_account;
-        let purchase = &mut ctx.accounts.purchase_record;
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_CLAIMS)?;
+        let presale = &ctx.accounts.presale_state;
         let clock = Clock::get()?;
 
-        require!(
-            clock.unix_timestamp >= vesting.start_timestamp + vesting.cliff_seconds as i64,
-            PresaleError::CliffNotReached
-        );
-
-        // Calculate vested amount
-        let elapsed_seconds = (clock.unix_timestamp - vesting.start_timestamp) as u64;
-        let elapsed_days = elapsed_seconds / 86400; // seconds per day
-
-        let vested_amount = if elapsed_days >= vesting.total_days {
-            vesting.total_amount // Fully vested
+        let claimable = if let Some(vesting) = ctx.accounts.vesting_account.as_mut() {
+            vesting.settle_claim(clock.unix_timestamp)?
         } else {
-            vesting
-                .total_amount
-                .checked_mul(vesting.daily_release_bps as u64)
-                .ok_or(PresaleError::MathOverflow)?
-                .checked_div(10000) // basis points
-                .ok_or(PresaleError::MathOverflow)?
-                .checked_mul(elapsed_days)
-                .ok_or(PresaleError::MathOverflow)?
+            let purchase = ctx
+                .accounts
+                .purchase_record
+                .as_mut()
+                .ok_or(PresaleError::NoVestingSchedule)?;
+            require!(
+                presale.is_finalized && !presale.refund_enabled,
+                PresaleError::PresaleNotFinalized
+            );
+            purchase.settle_claim(presale, clock.unix_timestamp)?
         };
-
-        let claimable = vested_amount
-            .checked_sub(vesting.claimed_amount)
-            .ok_or(PresaleError::MathOverflow)?;
-
         require!(claimable > 0, PresaleError::NothingToClaim);
 
         // Transfer tokens from escrow to beneficiary
-        let seeds = &[b"presale".as_ref(), &[ctx.accounts.presale_state.bump]];
+        let seeds = &[b"presale".as_ref(), &[presale.bump]];
         let signer = &[&seeds[..]];
 
         let transfer_ctx = CpiContext::new_with_signer(
@@ -199,239 +468,2708 @@ pub mod myxn_presale {
             Transfer {
                 from: ctx.accounts.escrow_token_account.to_account_info(),
                 to: ctx.accounts.beneficiary_token_account.to_account_info(),
-                authority: ctx.accounts.presale_state.to_account_info(),
+                authority: presale.to_account_info(),
             },
             signer,
         );
         token::transfer(transfer_ctx, claimable)?;
 
-        // Update state using checked arithmetic
-        vesting.claimed_amount = vesting
-            .claimed_amount
-            .checked_add(claimable)
-            .ok_or(PresaleError::MathOverflow)?;
-        purchase.claimed_tokens = purchase
-            .claimed_tokens
+        let presale = &mut ctx.accounts.presale_state;
+        presale.total_claimed = presale
+            .total_claimed
             .checked_add(claimable)
             .ok_or(PresaleError::MathOverflow)?;
-        purchase.last_claim_timestamp = clock.unix_timestamp;
 
-        msg!(
-            "Claimed {} tokens ({} total claimed)",
-            claimable,
-            vesting.claimed_amount
-        );
+        msg!("Claimed {} tokens", claimable);
 
         Ok(())
     }
 
-    /// Finalize presale and trigger LP creation if threshold met
-    pub fn finalize_presale(ctx: Context<FinalizePresale>) -> Result<()> {
-        let presale = &mut ctx.accounts.presale_state;
-
-        require!(presale.is_active, PresaleError::PresaleNotActive);
-        require!(!presale.is_finalized, PresaleError::PresaleFinalized);
-        require!(
-            ctx.accounts.admin.key() == presale.admin,
-            PresaleError::Unauthorized
-        );
-
-        presale.is_active = false;
-        presale.is_finalized = true;
+    /// Create a named allocation pool with its own token vault and cap (admin only)
+    pub fn initialize_allocation_pool(
+        ctx: Context<InitializeAllocationPool>,
+        kind: AllocationKind,
+        cap: u64,
+    ) -> Result<()> {
+        require_authority(
+            &ctx.accounts.presale_state.admin,
+            &ctx.accounts.admin,
+            ctx.remaining_accounts,
+        )?;
+        require!(cap > 0, PresaleError::InvalidAmount);
 
-        if presale.total_raised_usd >= presale.lp_min_threshold_usd {
-            // Threshold met - enable LP creation
-            msg!(
-                "Presale successful! Raised ${} (threshold: ${})",
-                presale.total_raised_usd,
-                presale.lp_min_threshold_usd
-            );
+        let pool = &mut ctx.accounts.allocation_pool;
+        pool.kind = kind;
+        pool.token_mint = ctx.accounts.token_mint.key();
+        pool.vault = ctx.accounts.vault.key();
+        pool.cap = cap;
+        pool.allocated = 0;
+        pool.claimed = 0;
+        pool.grant_count = 0;
+        pool.bump = ctx.bumps.allocation_pool;
 
-            // LP creation logic would go here
-            // This would involve calling Raydium/Orca AMM instructions
-            // For now, just emit event
-            emit!(PresaleSuccess {
-                total_raised: presale.total_raised_usd,
-                total_sold: presale.total_sold,
-            });
-        } else {
-            // Threshold not met - enable refunds
-            presale.refund_enabled = true;
-            msg!("Presale failed to meet threshold. Refunds enabled.");
+        emit!(AllocationPoolCreated {
+            pool: pool.key(),
+            kind,
+            cap,
+        });
 
-            emit!(PresaleFailed {
-                total_raised: presale.total_raised_usd,
-                threshold: presale.lp_min_threshold_usd,
-            });
-        }
+        msg!("Allocation pool {:?} created with cap {}", kind, cap);
 
         Ok(())
     }
 
-    /// Request refund if presale failed
-    pub fn refund_purchase(ctx: Context<RefundPurchase>) -> Result<()> {
-        let presale = &ctx.accounts.presale_state;
-        let purchase = &mut ctx.accounts.purchase_record;
-
-        require!(presale.refund_enabled, PresaleError::RefundNotEnabled);
-        require!(purchase.total_tokens > 0, PresaleError::NothingToRefund);
-        require!(purchase.claimed_tokens == 0, PresaleError::AlreadyClaimed);
+    /// Deposit tokens into an allocation pool vault (admin only)
+    pub fn fund_allocation_pool(ctx: Context<FundAllocationPool>, amount: u64) -> Result<()> {
+        require_authority(
+            &ctx.accounts.presale_state.admin,
+            &ctx.accounts.admin,
+            ctx.remaining_accounts,
+        )?;
+        require!(amount > 0, PresaleError::InvalidAmount);
 
-        // Calculate refund amount (in SOL)
-        let refund_amount = purchase.total_purchased_usd; // Simplified - would need SOL price conversion
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.admin_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, amount)?;
 
-        // Transfer SOL from escrow back to buyer
-        // Note: The escrow account is validated through PDA seeds in the account constraints
-        **ctx
-            .accounts
-            .escrow_account
-            .to_account_info()
-            .try_borrow_mut_lamports()? -= refund_amount;
-        **ctx
-            .accounts
-            .buyer
-            .to_account_info()
-            .try_borrow_mut_lamports()? += refund_amount;
+        ctx.accounts.vault.reload()?;
 
-        // Mark as refunded
-        purchase.total_tokens = 0;
-        purchase.total_purchased_usd = 0;
+        emit!(VaultFunded {
+            amount,
+            vault_balance: ctx.accounts.vault.amount,
+        });
 
-        msg!("Refunded {} to buyer", refund_amount);
+        msg!(
+            "Allocation pool funded with {} tokens ({} in vault)",
+            amount,
+            ctx.accounts.vault.amount
+        );
 
         Ok(())
     }
-}
 
-// ============================================================================
-// Accounts
-// ============================================================================
+    /// Grant a vesting schedule out of an allocation pool (admin only)
+    ///
+    /// Grants are keyed by (pool, beneficiary, index), so a wallet can hold
+    /// several schedules per pool. The pool's cap bounds the sum of all grants.
+    pub fn create_pool_vesting(
+        ctx: Context<CreatePoolVesting>,
+        index: u32,
+        vesting_params: VestingParams,
+    ) -> Result<()> {
+        require_authority(
+            &ctx.accounts.presale_state.admin,
+            &ctx.accounts.admin,
+            ctx.remaining_accounts,
+        )?;
+        require!(vesting_params.total_amount > 0, PresaleError::InvalidAmount);
+        require!(
+            vesting_params.schedule.is_valid(),
+            PresaleError::InvalidVestingSchedule
+        );
 
-#[derive(Accounts)]
-pub struct InitializePresale<'info> {
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + PresaleState::INIT_SPACE,
-        seeds = [b"presale"],
-        bump
-    )]
-    pub presale_state: Account<'info, PresaleState>,
+        let pool = &mut ctx.accounts.allocation_pool;
+        pool.allocated = pool
+            .allocated
+            .checked_add(vesting_params.total_amount)
+            .ok_or(PresaleError::MathOverflow)?;
+        require!(pool.allocated <= pool.cap, PresaleError::PoolCapExceeded);
+        pool.grant_count = pool
+            .grant_count
+            .checked_add(1)
+            .ok_or(PresaleError::MathOverflow)?;
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
+        let vesting = &mut ctx.accounts.vesting_account;
+        vesting.beneficiary = ctx.accounts.beneficiary.key();
+        vesting.pool = pool.key();
+        vesting.index = index;
+        vesting.total_amount = vesting_params.total_amount;
+        vesting.start_timestamp = vesting_params.start_timestamp;
+        vesting.schedule = vesting_params.schedule;
+        vesting.claimed_amount = 0;
+        vesting.revocable = vesting_params.revocable;
+        vesting.revoked = false;
+        vesting.claim_delegate = Pubkey::default();
+        vesting.approved_token_account = Pubkey::default();
+        vesting.bump = ctx.bumps.vesting_account;
 
-    pub token_mint: Account<'info, Mint>,
+        emit!(PoolVestingCreated {
+            pool: pool.key(),
+            beneficiary: vesting.beneficiary,
+            index,
+            total_amount: vesting.total_amount,
+            start_timestamp: vesting.start_timestamp,
+        });
 
-    /// CHECK: Treasury wallet
-    pub treasury: AccountInfo<'info>,
+        msg!(
+            "Pool grant #{} of {} tokens for {} ({} of {} allocated)",
+            index,
+            vesting.total_amount,
+            vesting.beneficiary,
+            pool.allocated,
+            pool.cap
+        );
 
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct PurchasePresale<'info> {
-    #[account(mut)]
-    pub presale_state: Account<'info, PresaleState>,
+    /// Claim vested tokens from an allocation pool grant
+    pub fn claim_pool_vesting(ctx: Context<ClaimPoolVesting>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_CLAIMS)?;
+        let clock = Clock::get()?;
+        let claimable = ctx
+            .accounts
+            .vesting_account
+            .settle_claim(clock.unix_timestamp)?;
+        require!(claimable > 0, PresaleError::NothingToClaim);
 
-    #[account(
-        init,
-        payer = buyer,
-        space = 8 + PurchaseRecord::INIT_SPACE,
-        seeds = [b"purchase", buyer.key().as_ref(), presale_state.key().as_ref()],
-        bump
-    )]
-    pub purchase_record: Account<'info, PurchaseRecord>,
+        let pool = &ctx.accounts.allocation_pool;
+        let seeds = &[
+            b"allocation_pool".as_ref(),
+            &[pool.kind as u8],
+            &[pool.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, claimable)?;
 
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+        let pool = &mut ctx.accounts.allocation_pool;
+        pool.claimed = pool
+            .claimed
+            .checked_add(claimable)
+            .ok_or(PresaleError::MathOverflow)?;
 
-    /// CHECK: Escrow account for SOL
-    #[account(mut)]
-    pub escrow_account: AccountInfo<'info>,
+        msg!("Claimed {} tokens from {:?} pool", claimable, pool.kind);
 
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeVesting<'info> {
-    #[account(
-        seeds = [b"presale"],
-        bump = presale_state.bump
-    )]
-    pub presale_state: Account<'info, PresaleState>,
+    /// Revoke a pool grant (admin only)
+    ///
+    /// The schedule is frozen at the amount vested now, which the beneficiary
+    /// can still claim; the unvested remainder returns to the treasury.
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_WITHDRAWALS)?;
+        require_authority(
+            &ctx.accounts.presale_state.admin,
+            &ctx.accounts.admin,
+            ctx.remaining_accounts,
+        )?;
 
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + VestingAccount::INIT_SPACE,
-        seeds = [b"vesting", beneficiary.key().as_ref()],
-        bump
-    )]
-    pub vesting_account: Account<'info, VestingAccount>,
+        let now = Clock::get()?.unix_timestamp;
+        let vesting = &mut ctx.accounts.vesting_account;
+        require!(vesting.revocable, PresaleError::NotRevocable);
+        require!(!vesting.revoked, PresaleError::AlreadyRevoked);
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
+        let vested_amount = vesting.vested_amount(now)?;
+        let unvested = vesting
+            .total_amount
+            .checked_sub(vested_amount)
+            .ok_or(PresaleError::MathOverflow)?;
+        vesting.total_amount = vested_amount;
+        vesting.revoked = true;
 
-    /// CHECK: Beneficiary wallet address
-    pub beneficiary: AccountInfo<'info>,
+        if unvested > 0 {
+            let pool = &ctx.accounts.allocation_pool;
+            let seeds = &[
+                b"allocation_pool".as_ref(),
+                &[pool.kind as u8],
+                &[pool.bump],
+            ];
+            let signer = &[&seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                signer,
+            );
+            token::transfer(transfer_ctx, unvested)?;
+        }
 
-    pub system_program: Program<'info, System>,
-}
+        // Clawed-back tokens left the vault, so they also leave the pool's allocation
+        let pool = &mut ctx.accounts.allocation_pool;
+        pool.allocated = pool
+            .allocated
+            .checked_sub(unvested)
+            .ok_or(PresaleError::MathOverflow)?;
+        pool.cap = pool
+            .cap
+            .checked_sub(unvested)
+            .ok_or(PresaleError::MathOverflow)?;
 
-#[derive(Accounts)]
-pub struct ClaimVested<'info> {
-    #[account(mut)]
-    pub presale_state: Account<'info, PresaleState>,
+        let vesting = &ctx.accounts.vesting_account;
+        emit!(VestingRevoked {
+            vesting_account: vesting.key(),
+            beneficiary: vesting.beneficiary,
+            vested_amount,
+            clawed_back: unvested,
+        });
 
-    #[account(
-        mut,
-        seeds = [b"vesting", beneficiary.key().as_ref()],
+        msg!(
+            "Revoked grant for {}: {} vested, {} returned to treasury",
+            vesting.beneficiary,
+            vested_amount,
+            unvested
+        );
+
+        Ok(())
+    }
+
+    /// Move a presale grant and/or purchase record to a new wallet
+    ///
+    /// Signed by the current beneficiary, plus the admin when the presale
+    /// requires co-signed transfers. Replacement PDAs are created under the new
+    /// wallet's seeds and the old ones are closed to the current beneficiary.
+    /// Only presale grants (`[b"vesting", beneficiary]`) move; allocation pool
+    /// grants stay with their beneficiary. Purchase records move only after
+    /// finalization, so a fresh record cannot reopen the wallet cap mid-sale.
+    pub fn transfer_vesting(ctx: Context<TransferVesting>) -> Result<()> {
+        let presale = &ctx.accounts.presale_state;
+        let from = ctx.accounts.beneficiary.key();
+        let to = ctx.accounts.new_beneficiary.key();
+
+        require!(from != to, PresaleError::InvalidBeneficiary);
+        if presale.vesting_transfer_requires_admin {
+            let admin = ctx
+                .accounts
+                .admin
+                .as_ref()
+                .ok_or(PresaleError::Unauthorized)?;
+            require_authority(&presale.admin, admin, ctx.remaining_accounts)?;
+        }
+
+        let accounts = &mut *ctx.accounts;
+        require!(
+            accounts.vesting_account.is_some() == accounts.new_vesting_account.is_some()
+                && accounts.purchase_record.is_some() == accounts.new_purchase_record.is_some(),
+            PresaleError::InvalidBeneficiary
+        );
+        require!(
+            accounts.vesting_account.is_some() || accounts.purchase_record.is_some(),
+            PresaleError::NoVestingSchedule
+        );
+
+        let mut vesting_moved = false;
+        if let (Some(old), Some(new)) = (
+            accounts.vesting_account.as_ref(),
+            accounts.new_vesting_account.as_mut(),
+        ) {
+            new.set_inner(VestingAccount {
+                beneficiary: to,
+                // Delegation was granted by the old wallet and does not carry over
+                claim_delegate: Pubkey::default(),
+                approved_token_account: Pubkey::default(),
+                bump: ctx.bumps.new_vesting_account,
+                ..(***old).clone()
+            });
+            vesting_moved = true;
+        }
+
+        let mut purchase_moved = false;
+        if let (Some(old), Some(new)) = (
+            accounts.purchase_record.as_ref(),
+            accounts.new_purchase_record.as_mut(),
+        ) {
+            require!(
+                accounts.presale_state.is_finalized,
+                PresaleError::PresaleNotFinalized
+            );
+            new.set_inner(PurchaseRecord {
+                buyer: to,
+                claim_delegate: Pubkey::default(),
+                approved_token_account: Pubkey::default(),
+                ..(***old).clone()
+            });
+            purchase_moved = true;
+        }
+
+        emit!(VestingTransferred {
+            from,
+            to,
+            vesting_moved,
+            purchase_moved,
+        });
+
+        msg!("Vesting transferred from {} to {}", from, to);
+
+        Ok(())
+    }
+
+    /// Claim from several vesting accounts in one transfer
+    ///
+    /// Vesting accounts are passed as writable remaining accounts and must all
+    /// belong to the signer and draw from the same source: the presale vault
+    /// when `allocation_pool` is omitted, otherwise that pool's vault. From the
+    /// presale vault the buyer's `purchase_record` can be settled in the same
+    /// transfer; pool grants are batched one pool per call.
+    pub fn claim_all<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimAll<'info>>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_CLAIMS)?;
+        let now = Clock::get()?.unix_timestamp;
+        let beneficiary = ctx.accounts.beneficiary.key();
+        let pool_key = ctx
+            .accounts
+            .allocation_pool
+            .as_ref()
+            .map_or(Pubkey::default(), |pool| pool.key());
+
+        require!(
+            !ctx.remaining_accounts.is_empty() || ctx.accounts.purchase_record.is_some(),
+            PresaleError::NoVestingSchedule
+        );
+
+        let mut total_claimable: u64 = 0;
+        if let Some(purchase) = ctx.accounts.purchase_record.as_mut() {
+            // Purchases are paid from the presale vault only
+            require!(
+                ctx.accounts.allocation_pool.is_none(),
+                PresaleError::InvalidVault
+            );
+            let presale = &ctx.accounts.presale_state;
+            require!(
+                presale.is_finalized && !presale.refund_enabled,
+                PresaleError::PresaleNotFinalized
+            );
+            total_claimable = purchase.settle_claim(presale, now)?;
+        }
+
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts.iter() {
+            // A repeated account would be paid twice off the same stale claimed_amount
+            require!(
+                !seen.contains(info.key),
+                PresaleError::DuplicateVestingAccount
+            );
+            seen.push(*info.key);
+            require!(info.is_writable, ErrorCode::AccountNotMutable);
+
+            let mut vesting = Account::<VestingAccount>::try_from(info)?;
+            require!(
+                vesting.beneficiary == beneficiary,
+                PresaleError::Unauthorized
+            );
+            require!(vesting.pool == pool_key, PresaleError::InvalidVault);
+
+            let claimable = vesting.settle_claim(now)?;
+            if claimable == 0 {
+                continue;
+            }
+            vesting.exit(&crate::ID)?;
+
+            total_claimable = total_claimable
+                .checked_add(claimable)
+                .ok_or(PresaleError::MathOverflow)?;
+
+            emit!(VestingClaimed {
+                vesting_account: info.key(),
+                beneficiary,
+                amount: claimable,
+                total_claimed: vesting.claimed_amount,
+            });
+        }
+        require!(total_claimable > 0, PresaleError::NothingToClaim);
+
+        let accounts = &mut *ctx.accounts;
+        pay_claim(
+            &mut accounts.presale_state,
+            accounts.allocation_pool.as_mut(),
+            &accounts.vault,
+            accounts.beneficiary_token_account.to_account_info(),
+            &accounts.token_program,
+            total_claimable,
+        )?;
+
+        msg!(
+            "Claimed {} tokens across {} schedules",
+            total_claimable,
+            seen.len()
+        );
+
+        Ok(())
+    }
+
+    /// Top up the lamport reserve that pays for ATAs created by `crank_claim`
+    pub fn fund_fee_reserve(ctx: Context<FundFeeReserve>, amount: u64) -> Result<()> {
+        require!(amount > 0, PresaleError::InvalidAmount);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.fee_reserve.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(transfer_ctx, amount)?;
+
+        msg!(
+            "Fee reserve funded with {} lamports ({} available)",
+            amount,
+            ctx.accounts.fee_reserve.lamports()
+        );
+
+        Ok(())
+    }
+
+    /// Push a beneficiary's vested presale tokens to their ATA (permissionless)
+    ///
+    /// Settles the presale grant and/or purchase record, whichever are passed.
+    /// A missing ATA is created with lamports from the fee reserve, so the
+    /// cranker only pays the transaction fee; once the reserve can no longer
+    /// cover it and stay rent-exempt, the cranker pays for the ATA instead.
+    pub fn crank_claim(ctx: Context<CrankClaim>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_CLAIMS)?;
+        let now = Clock::get()?.unix_timestamp;
+        let presale = &ctx.accounts.presale_state;
+
+        let mut claimable: u64 = 0;
+        if let Some(vesting) = ctx.accounts.vesting_account.as_mut() {
+            claimable = vesting.settle_claim(now)?;
+        }
+        if let Some(purchase) = ctx.accounts.purchase_record.as_mut() {
+            if presale.is_finalized && !presale.refund_enabled {
+                claimable = claimable
+                    .checked_add(purchase.settle_claim(presale, now)?)
+                    .ok_or(PresaleError::MathOverflow)?;
+            }
+        }
+        require!(claimable > 0, PresaleError::NothingToClaim);
+
+        let ata_created = ctx.accounts.beneficiary_token_account.data_is_empty();
+        if ata_created {
+            create_beneficiary_ata(
+                &ctx.accounts.fee_reserve,
+                ctx.bumps.fee_reserve,
+                &ctx.accounts.cranker,
+                &ctx.accounts.beneficiary_token_account,
+                &ctx.accounts.beneficiary,
+                &ctx.accounts.token_mint,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.token_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+
+        let seeds = &[b"presale".as_ref(), &[presale.bump]];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                authority: presale.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, claimable)?;
+
+        let presale = &mut ctx.accounts.presale_state;
+        presale.total_claimed = presale
+            .total_claimed
+            .checked_add(claimable)
+            .ok_or(PresaleError::MathOverflow)?;
+
+        emit!(CrankClaimed {
+            beneficiary: ctx.accounts.beneficiary.key(),
+            cranker: ctx.accounts.cranker.key(),
+            amount: claimable,
+            ata_created,
+        });
+
+        msg!(
+            "Pushed {} tokens to {}",
+            claimable,
+            ctx.accounts.beneficiary.key()
+        );
+
+        Ok(())
+    }
+
+    /// Push a beneficiary's vested pool grant to their ATA (permissionless)
+    ///
+    /// Same as `crank_claim`, for grants paid from an allocation pool's vault.
+    pub fn crank_pool_claim(ctx: Context<CrankPoolClaim>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_CLAIMS)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let claimable = ctx.accounts.vesting_account.settle_claim(now)?;
+        require!(claimable > 0, PresaleError::NothingToClaim);
+
+        let ata_created = ctx.accounts.beneficiary_token_account.data_is_empty();
+        if ata_created {
+            create_beneficiary_ata(
+                &ctx.accounts.fee_reserve,
+                ctx.bumps.fee_reserve,
+                &ctx.accounts.cranker,
+                &ctx.accounts.beneficiary_token_account,
+                &ctx.accounts.beneficiary,
+                &ctx.accounts.token_mint,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.token_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+
+        let accounts = &mut *ctx.accounts;
+        pay_claim(
+            &mut accounts.presale_state,
+            Some(&mut accounts.allocation_pool),
+            &accounts.vault,
+            accounts.beneficiary_token_account.to_account_info(),
+            &accounts.token_program,
+            claimable,
+        )?;
+
+        emit!(CrankClaimed {
+            beneficiary: accounts.beneficiary.key(),
+            cranker: accounts.cranker.key(),
+            amount: claimable,
+            ata_created,
+        });
+
+        msg!(
+            "Pushed {} pool tokens to {}",
+            claimable,
+            accounts.beneficiary.key()
+        );
+
+        Ok(())
+    }
+
+    /// Set who may claim on the beneficiary's behalf and where those claims pay out
+    ///
+    /// Pass `Pubkey::default()` as `claim_delegate` to revoke delegation. With a
+    /// default `approved_token_account`, delegated claims must pay into a token
+    /// account owned by the beneficiary.
+    pub fn set_claim_delegate(
+        ctx: Context<SetClaimDelegate>,
+        claim_delegate: Pubkey,
+        approved_token_account: Pubkey,
+    ) -> Result<()> {
+        let vesting = &mut ctx.accounts.vesting_account;
+        vesting.claim_delegate = claim_delegate;
+        vesting.approved_token_account = approved_token_account;
+
+        emit!(ClaimDelegateSet {
+            vesting_account: vesting.key(),
+            beneficiary: vesting.beneficiary,
+            claim_delegate,
+            approved_token_account,
+        });
+
+        msg!(
+            "Claim delegate for {} set to {}",
+            vesting.key(),
+            claim_delegate
+        );
+
+        Ok(())
+    }
+
+    /// Claim vested tokens as the vesting account's `claim_delegate`
+    ///
+    /// Tokens go only to the beneficiary's approved account, or to a token
+    /// account the beneficiary owns. Pool grants pass their `allocation_pool`.
+    pub fn delegate_claim(ctx: Context<DelegateClaim>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_CLAIMS)?;
+        let now = Clock::get()?.unix_timestamp;
+        let accounts = &mut *ctx.accounts;
+        let vesting = &mut accounts.vesting_account;
+
+        require!(
+            vesting.pool
+                == accounts
+                    .allocation_pool
+                    .as_ref()
+                    .map_or(Pubkey::default(), |pool| pool.key()),
+            PresaleError::InvalidVault
+        );
+        let destination = &accounts.destination_token_account;
+        let approved = if vesting.approved_token_account == Pubkey::default() {
+            destination.owner == vesting.beneficiary
+        } else {
+            destination.key() == vesting.approved_token_account
+        };
+        require!(approved, PresaleError::UnapprovedDestination);
+
+        let claimable = vesting.settle_claim(now)?;
+        require!(claimable > 0, PresaleError::NothingToClaim);
+
+        emit!(VestingClaimed {
+            vesting_account: vesting.key(),
+            beneficiary: vesting.beneficiary,
+            amount: claimable,
+            total_claimed: vesting.claimed_amount,
+        });
+
+        pay_claim(
+            &mut accounts.presale_state,
+            accounts.allocation_pool.as_mut(),
+            &accounts.vault,
+            accounts.destination_token_account.to_account_info(),
+            &accounts.token_program,
+            claimable,
+        )?;
+
+        msg!("Delegate claimed {} tokens", claimable);
+
+        Ok(())
+    }
+
+    /// Set who may claim a buyer's purchased tokens and where those claims pay out
+    ///
+    /// Same rules as `set_claim_delegate`, applied to the buyer's purchase record.
+    pub fn set_purchase_claim_delegate(
+        ctx: Context<SetPurchaseClaimDelegate>,
+        claim_delegate: Pubkey,
+        approved_token_account: Pubkey,
+    ) -> Result<()> {
+        let purchase = &mut ctx.accounts.purchase_record;
+        purchase.claim_delegate = claim_delegate;
+        purchase.approved_token_account = approved_token_account;
+
+        emit!(PurchaseClaimDelegateSet {
+            purchase_record: purchase.key(),
+            buyer: purchase.buyer,
+            claim_delegate,
+            approved_token_account,
+        });
+
+        msg!(
+            "Claim delegate for {} set to {}",
+            purchase.key(),
+            claim_delegate
+        );
+
+        Ok(())
+    }
+
+    /// Claim vested purchased tokens as the purchase record's `claim_delegate`
+    ///
+    /// Tokens go only to the buyer's approved account, or to a token account
+    /// the buyer owns.
+    pub fn delegate_claim_purchase(ctx: Context<DelegateClaimPurchase>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_CLAIMS)?;
+        let now = Clock::get()?.unix_timestamp;
+        let accounts = &mut *ctx.accounts;
+        let presale = &accounts.presale_state;
+        let purchase = &mut accounts.purchase_record;
+
+        require!(
+            presale.is_finalized && !presale.refund_enabled,
+            PresaleError::PresaleNotFinalized
+        );
+        let destination = &accounts.destination_token_account;
+        let approved = if purchase.approved_token_account == Pubkey::default() {
+            destination.owner == purchase.buyer
+        } else {
+            destination.key() == purchase.approved_token_account
+        };
+        require!(approved, PresaleError::UnapprovedDestination);
+
+        let claimable = purchase.settle_claim(presale, now)?;
+        require!(claimable > 0, PresaleError::NothingToClaim);
+
+        pay_claim(
+            &mut accounts.presale_state,
+            None,
+            &accounts.vault,
+            accounts.destination_token_account.to_account_info(),
+            &accounts.token_program,
+            claimable,
+        )?;
+
+        msg!("Delegate claimed {} purchased tokens", claimable);
+
+        Ok(())
+    }
+
+    /// Replace the pause bitmask (guardian only)
+    pub fn set_pause(ctx: Context<SetPause>, paused: u8) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, PresaleError::InvalidPauseFlags);
+
+        let presale = &mut ctx.accounts.presale_state;
+        let previous = presale.paused;
+        presale.paused = paused;
+
+        emit!(PauseUpdated {
+            guardian: ctx.accounts.guardian.key(),
+            previous,
+            paused,
+        });
+
+        msg!(
+            "Pause flags changed from {:#06b} to {:#06b}",
+            previous,
+            paused
+        );
+
+        Ok(())
+    }
+
+    /// Hand the guardian role to a new key (admin only)
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+
+        let previous = presale.guardian;
+        presale.guardian = guardian;
+
+        emit!(GuardianUpdated { previous, guardian });
+
+        msg!("Guardian changed from {} to {}", previous, guardian);
+
+        Ok(())
+    }
+
+    /// Nominate a new admin, who must accept before taking over (admin only)
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+
+        presale.pending_admin = new_admin;
+
+        emit!(AdminProposed {
+            admin: presale.admin,
+            pending_admin: new_admin,
+        });
+
+        msg!("Admin handover to {} proposed", new_admin);
+
+        Ok(())
+    }
+
+    /// Complete a handover started by `propose_admin` (pending admin only)
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+        require!(
+            presale.pending_admin != Pubkey::default(),
+            PresaleError::NoPendingAdmin
+        );
+        require_authority(
+            &presale.pending_admin,
+            &ctx.accounts.new_admin,
+            ctx.remaining_accounts,
+        )?;
+
+        let previous = presale.admin;
+        presale.admin = presale.pending_admin;
+        presale.pending_admin = Pubkey::default();
+
+        emit!(AdminAccepted {
+            previous,
+            admin: presale.admin,
+        });
+
+        msg!("Admin changed from {} to {}", previous, presale.admin);
+
+        Ok(())
+    }
+
+    /// Create the presale's M-of-N multisig (admin only)
+    ///
+    /// The multisig only takes effect once it is made admin through
+    /// `propose_admin`/`accept_admin`.
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require_authority(
+            &ctx.accounts.presale_state.admin,
+            &ctx.accounts.admin,
+            ctx.remaining_accounts,
+        )?;
+        require!(
+            Multisig::is_valid_set(&signers, threshold),
+            PresaleError::InvalidMultisig
+        );
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.presale = ctx.accounts.presale_state.key();
+        multisig.signers = signers;
+        multisig.threshold = threshold;
+        multisig.bump = ctx.bumps.multisig;
+
+        emit!(MultisigUpdated {
+            multisig: multisig.key(),
+            signers: multisig.signers.clone(),
+            threshold,
+        });
+
+        msg!(
+            "Multisig created: {} of {}",
+            threshold,
+            multisig.signers.len()
+        );
+
+        Ok(())
+    }
+
+    /// Replace the multisig's member set (current multisig threshold)
+    ///
+    /// `member` and the remaining-account co-signers must meet the current threshold.
+    pub fn update_multisig(
+        ctx: Context<UpdateMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            Multisig::is_valid_set(&signers, threshold),
+            PresaleError::InvalidMultisig
+        );
+
+        let multisig = &mut ctx.accounts.multisig;
+        require!(
+            multisig.is_approved(&signed_keys(&ctx.accounts.member, ctx.remaining_accounts)),
+            PresaleError::MultisigThresholdNotMet
+        );
+
+        multisig.signers = signers;
+        multisig.threshold = threshold;
+
+        emit!(MultisigUpdated {
+            multisig: multisig.key(),
+            signers: multisig.signers.clone(),
+            threshold,
+        });
+
+        msg!(
+            "Multisig updated: {} of {}",
+            threshold,
+            multisig.signers.len()
+        );
+
+        Ok(())
+    }
+
+    /// Queue a parameter change, applicable after `PARAMS_UPDATE_DELAY_SECS` (admin only)
+    ///
+    /// Replaces any change already queued. Fields left `None` are unchanged.
+    pub fn update_params(ctx: Context<UpdateParams>, update: ParamsUpdate) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        presale.check_params_update(&update, now)?;
+
+        let eta = now
+            .checked_add(PARAMS_UPDATE_DELAY_SECS)
+            .ok_or(PresaleError::MathOverflow)?;
+        presale.pending_params = Some(update);
+        presale.pending_params_eta = eta;
+
+        emit!(ParamsUpdateQueued { update, eta });
+
+        msg!("Parameter update queued, applicable from {}", eta);
+
+        Ok(())
+    }
+
+    /// Apply the queued parameter change once its ETA has passed (permissionless)
+    pub fn apply_params(ctx: Context<ApplyParams>) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+        let update = presale
+            .pending_params
+            .ok_or(PresaleError::NoPendingParams)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= presale.pending_params_eta,
+            PresaleError::ParamsTimelockActive
+        );
+        // Invariants are rechecked against the state at apply time
+        presale.check_params_update(&update, now)?;
+
+        if let Some(price_usd) = update.price_usd {
+            presale.price_usd = price_usd;
+        }
+        if let Some(max_per_wallet_usd) = update.max_per_wallet_usd {
+            presale.max_per_wallet_usd = max_per_wallet_usd;
+        }
+        if let Some(lp_min_threshold_usd) = update.lp_min_threshold_usd {
+            presale.lp_min_threshold_usd = lp_min_threshold_usd;
+        }
+        if let Some(fee_split) = update.fee_split {
+            presale.fee_split = fee_split;
+        }
+        presale.pending_params = None;
+        presale.pending_params_eta = 0;
+
+        emit!(ParamsUpdateApplied { update });
+
+        msg!("Parameter update applied");
+
+        Ok(())
+    }
+
+    /// Drop the queued parameter change (admin only)
+    pub fn cancel_params_update(ctx: Context<UpdateParams>) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+        require!(
+            presale.pending_params.is_some(),
+            PresaleError::NoPendingParams
+        );
+
+        presale.pending_params = None;
+        presale.pending_params_eta = 0;
+
+        emit!(ParamsUpdateCancelled {});
+
+        msg!("Parameter update cancelled");
+
+        Ok(())
+    }
+
+    /// Finalize presale and trigger LP creation if threshold met
+    ///
+    /// The admin may finalize at any time; anyone may once `end_ts` has passed
+    /// or the hard cap is sold out. On success the TGE is recorded once as the
+    /// vesting start for all purchases: `tge_timestamp` when the admin
+    /// finalizes, otherwise the current time.
+    pub fn finalize_presale(ctx: Context<FinalizePresale>, tge_timestamp: i64) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(presale.is_active, PresaleError::PresaleNotActive);
+        require!(!presale.is_finalized, PresaleError::PresaleFinalized);
+        let is_admin = require_authority(
+            &presale.admin,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )
+        .is_ok();
+        require!(
+            is_admin || presale.sale_closed(now),
+            PresaleError::SaleStillOpen
+        );
+        let tge_timestamp = if is_admin { tge_timestamp } else { now };
+
+        presale.is_active = false;
+        presale.is_finalized = true;
+
+        // Fair launch: scale every allocation down to the hard cap and keep
+        // only the matching share of the raise; the rest is claim_excess
+        if presale.total_sold > presale.presale_tokens {
+            let requested = presale.total_sold;
+            let cap = presale.presale_tokens;
+            presale.total_requested = requested;
+            presale.total_sold = cap;
+            presale.total_raised_usd = presale.filled(presale.total_raised_usd)?;
+
+            // Escrow keeps what claim_excess still owes out of fee distribution
+            presale.excess_lamports_owed = presale.excess(presale.total_raised_lamports)?;
+            presale.total_raised_lamports = presale.filled(presale.total_raised_lamports)?;
+            for slot in 0..MAX_SPL_MINTS {
+                let raised = presale.total_raised_spl[slot];
+                presale.excess_spl_owed[slot] = presale.excess(raised)?;
+                presale.total_raised_spl[slot] = presale.filled(raised)?;
+            }
+
+            emit!(PresaleOversubscribed {
+                total_requested: requested,
+                presale_tokens: cap,
+                total_raised: presale.total_raised_usd,
+            });
+        }
+
+        if presale.total_raised_usd >= presale.lp_min_threshold_usd {
+            // Threshold met - enable LP creation
+            msg!(
+                "Presale successful! Raised ${} (threshold: ${})",
+                presale.total_raised_usd,
+                presale.lp_min_threshold_usd
+            );
+
+            require!(tge_timestamp >= now, PresaleError::InvalidTgeTimestamp);
+            presale.tge_timestamp = tge_timestamp;
+
+            // LP is seeded separately through create_liquidity
+            emit!(PresaleSuccess {
+                total_raised: presale.total_raised_usd,
+                total_sold: presale.total_sold,
+            });
+        } else {
+            // Threshold not met - enable refunds
+            presale.refund_enabled = true;
+            msg!("Presale failed to meet threshold. Refunds enabled.");
+
+            emit!(PresaleFailed {
+                total_raised: presale.total_raised_usd,
+                threshold: presale.lp_min_threshold_usd,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Request refund if presale failed
+    ///
+    /// Returns the exact lamports the buyer deposited, plus the deposit for
+    /// one SPL mint when its escrow and buyer token accounts are supplied.
+    /// Buyers who paid with several stablecoins call this once per mint.
+    pub fn refund_purchase(ctx: Context<RefundPurchase>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_REFUNDS)?;
+        let presale = &ctx.accounts.presale_state;
+        let purchase = &mut ctx.accounts.purchase_record;
+
+        require!(presale.refund_enabled, PresaleError::RefundNotEnabled);
+        require!(purchase.has_deposits(), PresaleError::NothingToRefund);
+        require!(purchase.claimed_tokens == 0, PresaleError::AlreadyClaimed);
+
+        // Transfer SOL from escrow back to buyer
+        let refund_lamports = purchase.total_lamports;
+        if refund_lamports > 0 {
+            let escrow_seeds = &[b"escrow".as_ref(), &[ctx.bumps.escrow_account]];
+            let signer = &[&escrow_seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.escrow_account.to_account_info(),
+                    to: ctx.accounts.buyer.to_account_info(),
+                },
+                signer,
+            );
+            anchor_lang::system_program::transfer(transfer_ctx, refund_lamports)?;
+            purchase.total_lamports = 0;
+        }
+
+        // Transfer the SPL deposit for the supplied mint back to buyer
+        let mut refund_tokens = 0;
+        if let (Some(escrow_token_account), Some(buyer_token_account)) = (
+            ctx.accounts.escrow_token_account.as_ref(),
+            ctx.accounts.buyer_token_account.as_ref(),
+        ) {
+            let slot = presale
+                .spl_mint_slot(&escrow_token_account.mint)
+                .ok_or(PresaleError::UnsupportedSplMint)?;
+            refund_tokens = purchase.spl_deposits[slot];
+
+            if refund_tokens > 0 {
+                let seeds = &[b"presale".as_ref(), &[presale.bump]];
+                let signer = &[&seeds[..]];
+                let transfer_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: escrow_token_account.to_account_info(),
+                        to: buyer_token_account.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer,
+                );
+                token::transfer(transfer_ctx, refund_tokens)?;
+                purchase.spl_deposits[slot] = 0;
+            }
+        }
+
+        // Mark as refunded
+        purchase.total_tokens = 0;
+        purchase.total_purchased_usd = 0;
+
+        msg!(
+            "Refunded {} lamports and {} SPL tokens to buyer",
+            refund_lamports,
+            refund_tokens
+        );
+
+        Ok(())
+    }
+
+    /// Return the part of a deposit not filled in an oversubscribed sale
+    ///
+    /// Pays back the excess lamports, plus the excess for one SPL mint when its
+    /// escrow and buyer token accounts are supplied. Each leg can be claimed
+    /// once; buyers who paid with several stablecoins call this once per mint.
+    pub fn claim_excess(ctx: Context<ClaimExcess>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_REFUNDS)?;
+        let presale = &ctx.accounts.presale_state;
+        let purchase = &mut ctx.accounts.purchase_record;
+
+        require!(presale.is_finalized, PresaleError::PresaleNotFinalized);
+        require!(!presale.refund_enabled, PresaleError::RefundsActive);
+        require!(presale.is_oversubscribed(), PresaleError::NotOversubscribed);
+
+        let mut excess_lamports = 0;
+        if !purchase.excess_sol_claimed {
+            excess_lamports = presale.excess(purchase.total_lamports)?;
+            purchase.excess_sol_claimed = true;
+
+            if excess_lamports > 0 {
+                let escrow_seeds = &[b"escrow".as_ref(), &[ctx.bumps.escrow_account]];
+                let signer = &[&escrow_seeds[..]];
+                let transfer_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.escrow_account.to_account_info(),
+                        to: ctx.accounts.buyer.to_account_info(),
+                    },
+                    signer,
+                );
+                anchor_lang::system_program::transfer(transfer_ctx, excess_lamports)?;
+            }
+        }
+
+        let mut excess_tokens = 0;
+        let mut spl_slot = None;
+        let mut spl_mint = None;
+        if let (Some(escrow_token_account), Some(buyer_token_account)) = (
+            ctx.accounts.escrow_token_account.as_ref(),
+            ctx.accounts.buyer_token_account.as_ref(),
+        ) {
+            let slot = presale
+                .spl_mint_slot(&escrow_token_account.mint)
+                .ok_or(PresaleError::UnsupportedSplMint)?;
+            require!(
+                !purchase.excess_spl_claimed[slot],
+                PresaleError::ExcessAlreadyClaimed
+            );
+            excess_tokens = presale.excess(purchase.spl_deposits[slot])?;
+            purchase.excess_spl_claimed[slot] = true;
+            spl_slot = Some(slot);
+            spl_mint = Some(escrow_token_account.mint);
+
+            if excess_tokens > 0 {
+                let seeds = &[b"presale".as_ref(), &[presale.bump]];
+                let signer = &[&seeds[..]];
+                let transfer_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: escrow_token_account.to_account_info(),
+                        to: buyer_token_account.to_account_info(),
+                        authority: presale.to_account_info(),
+                    },
+                    signer,
+                );
+                token::transfer(transfer_ctx, excess_tokens)?;
+            }
+        }
+
+        require!(
+            excess_lamports > 0 || excess_tokens > 0,
+            PresaleError::NothingToRefund
+        );
+
+        let presale = &mut ctx.accounts.presale_state;
+        presale.excess_lamports_owed = presale
+            .excess_lamports_owed
+            .checked_sub(excess_lamports)
+            .ok_or(PresaleError::MathOverflow)?;
+        if let Some(slot) = spl_slot {
+            presale.excess_spl_owed[slot] = presale.excess_spl_owed[slot]
+                .checked_sub(excess_tokens)
+                .ok_or(PresaleError::MathOverflow)?;
+        }
+
+        emit!(ExcessClaimed {
+            buyer: ctx.accounts.buyer.key(),
+            lamports: excess_lamports,
+            spl_mint,
+            spl_amount: excess_tokens,
+        });
+
+        msg!(
+            "Returned {} excess lamports and {} excess SPL tokens to buyer",
+            excess_lamports,
+            excess_tokens
+        );
+
+        Ok(())
+    }
+
+    /// Seed the constant-product pool with the LP share of raised SOL (admin only)
+    ///
+    /// Wraps `lp_share_bps` of the raised lamports into wSOL and pairs it with
+    /// MYXN from the vault at `listing_price_usd`, then deposits both through the
+    /// configured AMM's `add_liquidity` instruction. The pool must be keyed
+    /// (mint_a = wSOL, mint_b = MYXN).
+    pub fn create_liquidity(ctx: Context<CreateLiquidity>, min_lp_out: u64) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_WITHDRAWALS)?;
+        let presale = &ctx.accounts.presale_state;
+
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+        require!(presale.is_finalized, PresaleError::PresaleNotFinalized);
+        require!(!presale.lp_created, PresaleError::LiquidityAlreadyCreated);
+        require!(
+            presale.total_raised_usd >= presale.lp_min_threshold_usd,
+            PresaleError::LpThresholdNotMet
+        );
+
+        // SOL side: configured share of everything raised in SOL, held back
+        // from fee distribution until now
+        let sol_amount = presale.lp_sol_amount();
+        require!(sol_amount > 0, PresaleError::InvalidAmount);
+
+        // MYXN side: same USD value at the listing price
+        let price = load_sol_price(
+            &ctx.accounts.sol_price_feed,
+            presale.oracle_max_age_secs,
+            presale.oracle_max_conf_bps,
+        )?;
+        let sol_usd = lamports_to_usd(sol_amount, &price)?;
+        let outstanding = presale.outstanding_tokens()?;
+        let token_amount = u64::try_from(
+            (sol_usd as u128)
+                .checked_mul(1_000_000_000) // 9 decimals
+                .ok_or(PresaleError::MathOverflow)?
+                / presale.listing_price_usd as u128,
+        )
+        .map_err(|_| error!(PresaleError::MathOverflow))?;
+        // Tokens owed to buyers and grantees are never paired into the pool
+        require!(
+            ctx.accounts
+                .vault
+                .amount
+                .checked_sub(token_amount)
+                .is_some_and(|left| left >= outstanding),
+            PresaleError::VaultInsolvent
+        );
+
+        // Wrap the SOL side into the presale-owned wSOL account
+        let escrow_seeds = &[b"escrow".as_ref(), &[ctx.bumps.escrow_account]];
+        let signer = &[&escrow_seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.escrow_account.to_account_info(),
+                to: ctx.accounts.wsol_account.to_account_info(),
+            },
+            signer,
+        );
+        anchor_lang::system_program::transfer(transfer_ctx, sol_amount)?;
+        token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SyncNative {
+                account: ctx.accounts.wsol_account.to_account_info(),
+            },
+        ))?;
+
+        // Deposit both sides into the pool, signing as the presale PDA
+        let ix = amm_add_liquidity_ix(
+            ctx.accounts.amm_program.key(),
+            ctx.accounts,
+            sol_amount,
+            token_amount,
+            min_lp_out,
+        );
+        let seeds = &[b"presale".as_ref(), &[presale.bump]];
+        let signer = &[&seeds[..]];
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.pool.to_account_info(),
+                ctx.accounts.presale_state.to_account_info(),
+                ctx.accounts.wsol_account.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.pool_vault_a.to_account_info(),
+                ctx.accounts.pool_vault_b.to_account_info(),
+                ctx.accounts.lp_mint.to_account_info(),
+                ctx.accounts.lp_lock_vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.amm_program.to_account_info(),
+            ],
+            signer,
+        )?;
+
+        ctx.accounts.lp_lock_vault.reload()?;
+        let lp_amount = ctx.accounts.lp_lock_vault.amount;
+        require!(lp_amount >= min_lp_out, PresaleError::SlippageExceeded);
+
+        let presale = &mut ctx.accounts.presale_state;
+        presale.lp_created = true;
+        presale.lp_mint = ctx.accounts.lp_mint.key();
+        presale.lp_amount = lp_amount;
+
+        emit!(LiquidityCreated {
+            lp_mint: presale.lp_mint,
+            lp_amount,
+            sol_amount,
+            token_amount,
+        });
+
+        // Lock the LP tokens for the configured duration
+        let now = Clock::get()?.unix_timestamp;
+        let unlock_timestamp = now
+            .checked_add(presale.lp_lock_seconds)
+            .ok_or(PresaleError::MathOverflow)?;
+        let release_end_timestamp = unlock_timestamp
+            .checked_add(presale.lp_release_seconds)
+            .ok_or(PresaleError::MathOverflow)?;
+
+        let lp_lock = &mut ctx.accounts.lp_lock;
+        lp_lock.presale = presale.key();
+        lp_lock.lp_mint = presale.lp_mint;
+        lp_lock.vault = ctx.accounts.lp_lock_vault.key();
+        lp_lock.total_amount = lp_amount;
+        lp_lock.released_amount = 0;
+        lp_lock.locked_at = now;
+        lp_lock.unlock_timestamp = unlock_timestamp;
+        lp_lock.release_end_timestamp = release_end_timestamp;
+        lp_lock.bump = ctx.bumps.lp_lock;
+
+        emit!(LiquidityLocked {
+            lp_mint: lp_lock.lp_mint,
+            amount: lp_amount,
+            unlock_timestamp,
+            release_end_timestamp,
+        });
+
+        msg!(
+            "Liquidity created: {} lamports + {} MYXN for {} LP",
+            sol_amount,
+            token_amount,
+            lp_amount
+        );
+
+        Ok(())
+    }
+
+    /// Release unlocked LP tokens to the treasury (treasury only)
+    ///
+    /// Nothing is releasable before `unlock_timestamp`; after it LP tokens
+    /// release linearly until `release_end_timestamp` (all at once if equal).
+    pub fn unlock_liquidity(ctx: Context<UnlockLiquidity>) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_WITHDRAWALS)?;
+        let now = Clock::get()?.unix_timestamp;
+        let lp_lock = &ctx.accounts.lp_lock;
+
+        require!(
+            now >= lp_lock.unlock_timestamp,
+            PresaleError::LiquidityStillLocked
+        );
+
+        let releasable = lp_lock
+            .unlocked_amount(now)?
+            .checked_sub(lp_lock.released_amount)
+            .ok_or(PresaleError::MathOverflow)?;
+        require!(releasable > 0, PresaleError::NothingToClaim);
+
+        let presale_key = ctx.accounts.presale_state.key();
+        let seeds = &[b"lp_lock".as_ref(), presale_key.as_ref(), &[lp_lock.bump]];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.treasury_lp_account.to_account_info(),
+                authority: ctx.accounts.lp_lock.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, releasable)?;
+
+        let lp_lock = &mut ctx.accounts.lp_lock;
+        lp_lock.released_amount = lp_lock
+            .released_amount
+            .checked_add(releasable)
+            .ok_or(PresaleError::MathOverflow)?;
+
+        emit!(LiquidityUnlocked {
+            lp_mint: lp_lock.lp_mint,
+            amount: releasable,
+            total_released: lp_lock.released_amount,
+        });
+
+        msg!(
+            "Unlocked {} LP tokens ({} of {} released)",
+            releasable,
+            lp_lock.released_amount,
+            lp_lock.total_amount
+        );
+
+        Ok(())
+    }
+
+    /// Deposit sale inventory into the presale-owned token vault (admin only)
+    pub fn fund_vault(ctx: Context<FundVault>, amount: u64) -> Result<()> {
+        require_authority(
+            &ctx.accounts.presale_state.admin,
+            &ctx.accounts.admin,
+            ctx.remaining_accounts,
+        )?;
+        require!(amount > 0, PresaleError::InvalidAmount);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.admin_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, amount)?;
+
+        ctx.accounts.vault.reload()?;
+
+        emit!(VaultFunded {
+            amount,
+            vault_balance: ctx.accounts.vault.amount,
+        });
+
+        msg!(
+            "Vault funded with {} tokens ({} in vault)",
+            amount,
+            ctx.accounts.vault.amount
+        );
+
+        Ok(())
+    }
+
+    /// Assert the vault covers all sold or granted but unclaimed tokens
+    pub fn check_solvency(ctx: Context<CheckSolvency>) -> Result<()> {
+        let presale = &ctx.accounts.presale_state;
+        let vault_balance = ctx.accounts.vault.amount;
+
+        let outstanding = presale.outstanding_tokens()?;
+        let solvent = vault_balance >= outstanding;
+
+        // Emitted before the assertion so simulations surface the figures either way
+        emit!(SolvencyChecked {
+            vault_balance,
+            outstanding,
+            solvent,
+        });
+
+        require!(solvent, PresaleError::VaultInsolvent);
+
+        Ok(())
+    }
+
+    /// Set the whitelist Merkle root and whether purchases require a proof (admin only)
+    pub fn set_whitelist(
+        ctx: Context<SetWhitelist>,
+        whitelist_root: [u8; 32],
+        whitelist_only: bool,
+    ) -> Result<()> {
+        let presale = &mut ctx.accounts.presale_state;
+
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+
+        presale.whitelist_root = whitelist_root;
+        presale.whitelist_only = whitelist_only;
+
+        emit!(WhitelistUpdated {
+            whitelist_root,
+            whitelist_only,
+        });
+
+        Ok(())
+    }
+
+    /// Split escrowed SOL and SPL fees between burn, charity, liquidity and treasury (admin only)
+    pub fn distribute_fees(
+        ctx: Context<DistributeFees>,
+        sol_amount: u64,
+        token_amount: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .presale_state
+            .require_not_paused(PAUSE_WITHDRAWALS)?;
+        let presale = &ctx.accounts.presale_state;
+
+        require_authority(&presale.admin, &ctx.accounts.admin, ctx.remaining_accounts)?;
+        require!(presale.is_finalized, PresaleError::PresaleNotFinalized);
+        require!(!presale.refund_enabled, PresaleError::RefundsActive);
+        require!(
+            sol_amount > 0 || token_amount > 0,
+            PresaleError::NothingToDistribute
+        );
+
+        if sol_amount > 0 {
+            // Excess deposits owed back to oversubscribed buyers, and the LP
+            // share until the pool is seeded, stay in escrow
+            let lp_reserve = if presale.lp_created {
+                0
+            } else {
+                presale.lp_sol_amount()
+            };
+            let reserved = presale
+                .excess_lamports_owed
+                .checked_add(lp_reserve)
+                .ok_or(PresaleError::MathOverflow)?;
+            require!(
+                ctx.accounts
+                    .escrow_account
+                    .lamports()
+                    .checked_sub(sol_amount)
+                    .is_some_and(|left| left >= reserved),
+                PresaleError::ExcessReserved
+            );
+            let shares = presale.fee_split.split(sol_amount)?;
+
+            let escrow_seeds = &[b"escrow".as_ref(), &[ctx.bumps.escrow_account]];
+            let signer = &[&escrow_seeds[..]];
+            let recipients = [
+                ctx.accounts.burn_wallet.to_account_info(),
+                ctx.accounts.charity_wallet.to_account_info(),
+                ctx.accounts.liquidity_wallet.to_account_info(),
+                ctx.accounts.treasury.to_account_info(),
+            ];
+
+            for (recipient, share) in recipients.into_iter().zip(shares) {
+                if share == 0 {
+                    continue;
+                }
+                let transfer_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.escrow_account.to_account_info(),
+                        to: recipient,
+                    },
+                    signer,
+                );
+                anchor_lang::system_program::transfer(transfer_ctx, share)?;
+            }
+
+            emit!(FeesDistributed {
+                mint: None,
+                total_amount: sol_amount,
+                burn_amount: shares[0],
+                charity_amount: shares[1],
+                liquidity_amount: shares[2],
+                treasury_amount: shares[3],
+            });
+        }
+
+        if token_amount > 0 {
+            let (
+                Some(fee_token_account),
+                Some(burn_token_account),
+                Some(charity_token_account),
+                Some(liquidity_token_account),
+                Some(treasury_token_account),
+            ) = (
+                ctx.accounts.fee_token_account.as_ref(),
+                ctx.accounts.burn_token_account.as_ref(),
+                ctx.accounts.charity_token_account.as_ref(),
+                ctx.accounts.liquidity_token_account.as_ref(),
+                ctx.accounts.treasury_token_account.as_ref(),
+            )
+            else {
+                return err!(PresaleError::FeeTokenAccountMissing);
+            };
+
+            // Only stablecoin escrows hold fees; the MYXN vault belongs to buyers
+            let slot = presale
+                .spl_mint_slot(&fee_token_account.mint)
+                .ok_or(PresaleError::UnsupportedSplMint)?;
+            require!(
+                fee_token_account.mint != presale.token_mint
+                    && fee_token_account.key()
+                        == get_associated_token_address(&presale.key(), &fee_token_account.mint),
+                PresaleError::InvalidFeeEscrow
+            );
+            require!(
+                fee_token_account
+                    .amount
+                    .checked_sub(token_amount)
+                    .is_some_and(|left| left >= presale.excess_spl_owed[slot]),
+                PresaleError::ExcessReserved
+            );
+            let shares = presale.fee_split.split(token_amount)?;
+
+            let seeds = &[b"presale".as_ref(), &[presale.bump]];
+            let signer = &[&seeds[..]];
+            let recipients = [
+                burn_token_account.to_account_info(),
+                charity_token_account.to_account_info(),
+                liquidity_token_account.to_account_info(),
+                treasury_token_account.to_account_info(),
+            ];
+
+            for (recipient, share) in recipients.into_iter().zip(shares) {
+                if share == 0 {
+                    continue;
+                }
+                let transfer_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: fee_token_account.to_account_info(),
+                        to: recipient,
+                        authority: presale.to_account_info(),
+                    },
+                    signer,
+                );
+                token::transfer(transfer_ctx, share)?;
+            }
+
+            emit!(FeesDistributed {
+                mint: Some(fee_token_account.mint),
+                total_amount: token_amount,
+                burn_amount: shares[0],
+                charity_amount: shares[1],
+                liquidity_amount: shares[2],
+                treasury_amount: shares[3],
+            });
+        }
+
+        msg!(
+            "Fees distributed: {} lamports, {} tokens",
+            sol_amount,
+            token_amount
+        );
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializePresale<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PresaleState::INIT_SPACE,
+        seeds = [b"presale"],
+        bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    /// CHECK: Treasury wallet
+    pub treasury: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurchasePresale<'info> {
+    #[account(mut)]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + PurchaseRecord::INIT_SPACE,
+        seeds = [b"purchase", buyer.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub purchase_record: Account<'info, PurchaseRecord>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Escrow account controlled by PDA, verified through seeds
+    #[account(
+        mut,
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow_account: AccountInfo<'info>,
+
+    /// CHECK: Pyth SOL/USD price account, verified against presale state and parsed on use
+    #[account(address = presale_state.sol_price_feed @ PresaleError::InvalidOracleAccount)]
+    pub sol_price_feed: AccountInfo<'info>,
+
+    /// Stablecoin mint for the SPL leg (required when `spl_amount` > 0)
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Presale-owned ATA for `payment_mint`; the escrow fee distribution draws from
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = presale_state
+    )]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVesting<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VestingAccount::INIT_SPACE,
+        seeds = [b"vesting", beneficiary.key().as_ref()],
+        bump
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: Beneficiary wallet address
+    pub beneficiary: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    /// Admin-created grant; omit to claim purchased tokens
+    #[account(
+        mut,
+        seeds = [b"vesting", beneficiary.key().as_ref()],
+        bump = vesting_account.bump
+    )]
+    pub vesting_account: Option<Account<'info, VestingAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"purchase", beneficiary.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub purchase_record: Option<Account<'info, PurchaseRecord>>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = presale_state.token_mint,
+        associated_token::authority = presale_state
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = presale_state.token_mint,
+        token::authority = beneficiary
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: AllocationKind)]
+pub struct InitializeAllocationPool<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AllocationPool::INIT_SPACE,
+        seeds = [b"allocation_pool".as_ref(), &[kind as u8]],
+        bump
+    )]
+    pub allocation_pool: Account<'info, AllocationPool>,
+
+    #[account(address = presale_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = token_mint,
+        associated_token::authority = allocation_pool
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundAllocationPool<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        seeds = [b"allocation_pool".as_ref(), &[allocation_pool.kind as u8]],
+        bump = allocation_pool.bump,
+        has_one = vault
+    )]
+    pub allocation_pool: Account<'info, AllocationPool>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = allocation_pool.token_mint,
+        token::authority = admin
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct CreatePoolVesting<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        mut,
+        seeds = [b"allocation_pool".as_ref(), &[allocation_pool.kind as u8]],
+        bump = allocation_pool.bump
+    )]
+    pub allocation_pool: Account<'info, AllocationPool>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VestingAccount::INIT_SPACE,
+        seeds = [
+            b"vesting",
+            allocation_pool.key().as_ref(),
+            beneficiary.key().as_ref(),
+            &index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: Beneficiary wallet address
+    pub beneficiary: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPoolVesting<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        mut,
+        seeds = [b"allocation_pool".as_ref(), &[allocation_pool.kind as u8]],
+        bump = allocation_pool.bump,
+        has_one = vault
+    )]
+    pub allocation_pool: Account<'info, AllocationPool>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"vesting",
+            allocation_pool.key().as_ref(),
+            beneficiary.key().as_ref(),
+            &vesting_account.index.to_le_bytes()
+        ],
+        bump = vesting_account.bump
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = allocation_pool.token_mint,
+        token::authority = beneficiary
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        mut,
+        seeds = [b"allocation_pool".as_ref(), &[allocation_pool.kind as u8]],
+        bump = allocation_pool.bump,
+        has_one = vault
+    )]
+    pub allocation_pool: Account<'info, AllocationPool>,
+
+    #[account(
+        mut,
+        constraint = vesting_account.pool == allocation_pool.key() @ PresaleError::NotRevocable
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = allocation_pool.token_mint,
+        token::authority = presale_state.treasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TransferVesting<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        mut,
+        close = beneficiary,
+        seeds = [b"vesting", beneficiary.key().as_ref()],
+        bump = vesting_account.bump
+    )]
+    pub vesting_account: Option<Box<Account<'info, VestingAccount>>>,
+
+    #[account(
+        init,
+        payer = beneficiary,
+        space = 8 + VestingAccount::INIT_SPACE,
+        seeds = [b"vesting", new_beneficiary.key().as_ref()],
+        bump
+    )]
+    pub new_vesting_account: Option<Box<Account<'info, VestingAccount>>>,
+
+    #[account(
+        mut,
+        close = beneficiary,
+        seeds = [b"purchase", beneficiary.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub purchase_record: Option<Box<Account<'info, PurchaseRecord>>>,
+
+    #[account(
+        init,
+        payer = beneficiary,
+        space = 8 + PurchaseRecord::INIT_SPACE,
+        seeds = [b"purchase", new_beneficiary.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub new_purchase_record: Option<Box<Account<'info, PurchaseRecord>>>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    /// CHECK: Destination wallet; only used as a PDA seed and recorded owner
+    pub new_beneficiary: AccountInfo<'info>,
+
+    /// Required when `vesting_transfer_requires_admin` is set
+    pub admin: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAll<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Box<Account<'info, PresaleState>>,
+
+    /// Source pool; omit to claim presale grants from the presale vault
+    #[account(
+        mut,
+        seeds = [b"allocation_pool".as_ref(), &[allocation_pool.kind as u8]],
+        bump = allocation_pool.bump
+    )]
+    pub allocation_pool: Option<Account<'info, AllocationPool>>,
+
+    /// Buyer's purchases; only with the presale vault as source
+    #[account(
+        mut,
+        seeds = [b"purchase", beneficiary.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub purchase_record: Option<Box<Account<'info, PurchaseRecord>>>,
+
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = presale_state.token_mint,
+        token::authority = beneficiary
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundFeeReserve<'info> {
+    /// CHECK: System-owned lamport reserve, verified through seeds
+    #[account(
+        mut,
+        seeds = [b"fee_reserve"],
+        bump
+    )]
+    pub fee_reserve: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Box<Account<'info, PresaleState>>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", beneficiary.key().as_ref()],
+        bump = vesting_account.bump
+    )]
+    pub vesting_account: Option<Box<Account<'info, VestingAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [b"purchase", beneficiary.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub purchase_record: Option<Box<Account<'info, PurchaseRecord>>>,
+
+    /// CHECK: Beneficiary wallet; only used as a PDA seed and ATA owner
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(address = presale_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = presale_state.token_mint,
+        associated_token::authority = presale_state
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Beneficiary's ATA, created from the fee reserve if missing
+    #[account(
+        mut,
+        address = get_associated_token_address(&beneficiary.key(), &presale_state.token_mint)
+    )]
+    pub beneficiary_token_account: AccountInfo<'info>,
+
+    /// CHECK: System-owned lamport reserve, verified through seeds
+    #[account(
+        mut,
+        seeds = [b"fee_reserve"],
+        bump
+    )]
+    pub fee_reserve: AccountInfo<'info>,
+
+    /// Pays for a missing ATA once the fee reserve runs low
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankPoolClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Box<Account<'info, PresaleState>>,
+
+    #[account(
+        mut,
+        seeds = [b"allocation_pool".as_ref(), &[allocation_pool.kind as u8]],
+        bump = allocation_pool.bump,
+        has_one = vault
+    )]
+    pub allocation_pool: Account<'info, AllocationPool>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"vesting",
+            allocation_pool.key().as_ref(),
+            beneficiary.key().as_ref(),
+            &vesting_account.index.to_le_bytes()
+        ],
         bump = vesting_account.bump
     )]
-    pub vesting_account: Account<'info, VestingAccount>,
+    pub vesting_account: Box<Account<'info, VestingAccount>>,
+
+    /// CHECK: Beneficiary wallet; only used as a PDA seed and ATA owner
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(address = allocation_pool.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// CHECK: Beneficiary's ATA, created from the fee reserve if missing
+    #[account(
+        mut,
+        address = get_associated_token_address(&beneficiary.key(), &allocation_pool.token_mint)
+    )]
+    pub beneficiary_token_account: AccountInfo<'info>,
+
+    /// CHECK: System-owned lamport reserve, verified through seeds
+    #[account(
+        mut,
+        seeds = [b"fee_reserve"],
+        bump
+    )]
+    pub fee_reserve: AccountInfo<'info>,
+
+    /// Pays for a missing ATA once the fee reserve runs low
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimDelegate<'info> {
+    #[account(mut, has_one = beneficiary @ PresaleError::Unauthorized)]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    pub beneficiary: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateClaim<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Box<Account<'info, PresaleState>>,
+
+    /// Source pool for pool grants; omit for presale grants
+    #[account(
+        mut,
+        seeds = [b"allocation_pool".as_ref(), &[allocation_pool.kind as u8]],
+        bump = allocation_pool.bump
+    )]
+    pub allocation_pool: Option<Account<'info, AllocationPool>>,
+
+    #[account(
+        mut,
+        constraint = vesting_account.claim_delegate == delegate.key() @ PresaleError::Unauthorized
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+
+    pub delegate: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = presale_state.token_mint)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetPurchaseClaimDelegate<'info> {
+    #[account(mut, has_one = buyer @ PresaleError::Unauthorized)]
+    pub purchase_record: Account<'info, PurchaseRecord>,
+
+    pub buyer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateClaimPurchase<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Box<Account<'info, PresaleState>>,
+
+    #[account(
+        mut,
+        seeds = [b"purchase", purchase_record.buyer.as_ref(), presale_state.key().as_ref()],
+        bump,
+        constraint = purchase_record.claim_delegate == delegate.key() @ PresaleError::Unauthorized
+    )]
+    pub purchase_record: Account<'info, PurchaseRecord>,
+
+    pub delegate: Signer<'info>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = presale_state.token_mint)]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump,
+        has_one = guardian @ PresaleError::Unauthorized
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    /// The pending admin, or a member of the pending multisig
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [b"multisig", presale_state.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMultisig<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.presale.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    pub member: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateParams<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyParams<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizePresale<'info> {
+    #[account(mut)]
+    pub presale_state: Account<'info, PresaleState>,
+
+    /// Admin, or anyone once the sale has ended or sold out
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundPurchase<'info> {
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        mut,
+        seeds = [b"purchase", buyer.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub purchase_record: Account<'info, PurchaseRecord>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Escrow account controlled by PDA, verified through seeds
+    #[account(
+        mut,
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow_account: AccountInfo<'info>,
+
+    #[account(mut, token::authority = presale_state)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ PresaleError::Unauthorized
+    )]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimExcess<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        mut,
+        seeds = [b"purchase", buyer.key().as_ref(), presale_state.key().as_ref()],
+        bump
+    )]
+    pub purchase_record: Account<'info, PurchaseRecord>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Escrow account controlled by PDA, verified through seeds
+    #[account(
+        mut,
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow_account: AccountInfo<'info>,
+
+    #[account(mut, token::authority = presale_state)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ PresaleError::Unauthorized
+    )]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Box<Account<'info, PresaleState>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: Pyth SOL/USD price account, verified against presale state and parsed on use
+    #[account(address = presale_state.sol_price_feed @ PresaleError::InvalidOracleAccount)]
+    pub sol_price_feed: AccountInfo<'info>,
 
+    /// CHECK: Escrow account controlled by PDA, verified through seeds
     #[account(
         mut,
-        seeds = [b"purchase", beneficiary.key().as_ref(), presale_state.key().as_ref()],
+        seeds = [b"escrow"],
         bump
     )]
-    pub purchase_record: Account<'info, PurchaseRecord>,
+    pub escrow_account: AccountInfo<'info>,
+
+    #[account(address = spl_token::native_mint::ID)]
+    pub native_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = native_mint,
+        associated_token::authority = presale_state
+    )]
+    pub wsol_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = presale_state.token_mint,
+        associated_token::authority = presale_state
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: AMM program, verified against presale state
+    #[account(address = presale_state.amm_program @ PresaleError::InvalidAmmProgram)]
+    pub amm_program: AccountInfo<'info>,
+
+    /// CHECK: Pool state, validated by the AMM program
+    pub pool: AccountInfo<'info>,
 
+    /// CHECK: Pool wSOL reserve, validated by the AMM program
     #[account(mut)]
-    pub beneficiary: Signer<'info>,
+    pub pool_vault_a: AccountInfo<'info>,
 
+    /// CHECK: Pool MYXN reserve, validated by the AMM program
     #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub pool_vault_b: AccountInfo<'info>,
 
     #[account(mut)]
-    pub beneficiary_token_account: Account<'info, TokenAccount>,
+    pub lp_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + LpLock::INIT_SPACE,
+        seeds = [b"lp_lock", presale_state.key().as_ref()],
+        bump
+    )]
+    pub lp_lock: Box<Account<'info, LpLock>>,
+
+    /// LP tokens are minted straight into the lock vault
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"lp_lock_vault", lp_lock.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = lp_lock
+    )]
+    pub lp_lock_vault: Box<Account<'info, TokenAccount>>,
 
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct FinalizePresale<'info> {
-    #[account(mut)]
+pub struct UnlockLiquidity<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_lock", presale_state.key().as_ref()],
+        bump = lp_lock.bump
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+
+    #[account(mut, address = lp_lock.vault @ PresaleError::InvalidLpLockVault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(address = presale_state.treasury @ PresaleError::Unauthorized)]
+    pub treasury: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = lp_lock.lp_mint,
+        token::authority = treasury
+    )]
+    pub treasury_lp_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundVault<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
     pub presale_state: Account<'info, PresaleState>,
 
     #[account(mut)]
     pub admin: Signer<'info>,
+
+    #[account(address = presale_state.token_mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = admin
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = token_mint,
+        associated_token::authority = presale_state
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RefundPurchase<'info> {
+pub struct CheckSolvency<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
     pub presale_state: Account<'info, PresaleState>,
 
+    #[account(
+        associated_token::mint = presale_state.token_mint,
+        associated_token::authority = presale_state
+    )]
+    pub vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetWhitelist<'info> {
     #[account(
         mut,
-        seeds = [b"purchase", buyer.key().as_ref(), presale_state.key().as_ref()],
-        bump
+        seeds = [b"presale"],
+        bump = presale_state.bump
     )]
-    pub purchase_record: Account<'info, PurchaseRecord>,
+    pub presale_state: Account<'info, PresaleState>,
 
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    #[account(
+        seeds = [b"presale"],
+        bump = presale_state.bump
+    )]
+    pub presale_state: Account<'info, PresaleState>,
+
+    pub admin: Signer<'info>,
 
     /// CHECK: Escrow account controlled by PDA, verified through seeds
     #[account(
@@ -440,6 +3178,53 @@ pub struct RefundPurchase<'info> {
         bump
     )]
     pub escrow_account: AccountInfo<'info>,
+
+    /// CHECK: Burn wallet, verified against presale state
+    #[account(mut, address = presale_state.burn_wallet)]
+    pub burn_wallet: AccountInfo<'info>,
+
+    /// CHECK: Charity wallet, verified against presale state
+    #[account(mut, address = presale_state.charity_wallet)]
+    pub charity_wallet: AccountInfo<'info>,
+
+    /// CHECK: Liquidity wallet, verified against presale state
+    #[account(mut, address = presale_state.liquidity_wallet)]
+    pub liquidity_wallet: AccountInfo<'info>,
+
+    /// CHECK: Treasury wallet, verified against presale state
+    #[account(mut, address = presale_state.treasury)]
+    pub treasury: AccountInfo<'info>,
+
+    /// Presale escrow ATA of an accepted SPL mint (required when distributing tokens)
+    #[account(mut, token::authority = presale_state)]
+    pub fee_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = burn_token_account.owner == presale_state.burn_wallet @ PresaleError::InvalidFeeRecipient
+    )]
+    pub burn_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = charity_token_account.owner == presale_state.charity_wallet @ PresaleError::InvalidFeeRecipient
+    )]
+    pub charity_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = liquidity_token_account.owner == presale_state.liquidity_wallet @ PresaleError::InvalidFeeRecipient
+    )]
+    pub liquidity_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == presale_state.treasury @ PresaleError::InvalidFeeRecipient
+    )]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 // ============================================================================
@@ -454,22 +3239,167 @@ pub struct PresaleState {
     pub treasury: Pubkey,
     pub burn_wallet: Pubkey,
     pub charity_wallet: Pubkey,
+    pub liquidity_wallet: Pubkey,
+    pub fee_split: FeeSplit,
+    pub accepted_spl_mints: [Pubkey; MAX_SPL_MINTS], // Pubkey::default() = unused slot
 
-    pub presale_tokens: u64,
+    pub sol_price_feed: Pubkey,
+    pub oracle_max_age_secs: u64,
+    pub oracle_max_conf_bps: u16, // max confidence interval relative to price
+    pub max_slippage_bps: u16,    // max deviation between buyer quote and oracle value
+
+    pub whitelist_root: [u8; 32],
+    pub whitelist_only: bool, // private round: purchases require a Merkle proof
+
+    pub vesting_transfer_requires_admin: bool, // transfer_vesting needs an admin co-sign
+
+    pub vesting_schedule: VestingSchedule, // schedule applied to every purchase
+    pub tge_timestamp: i64,                // set once at successful finalization
+
+    pub presale_tokens: u64,    // hard cap
+    pub oversubscription: bool, // fair launch: accept buys past the cap, pro-rate at finalize
     pub price_usd: u64,
     pub max_per_wallet_usd: u64,
     pub lp_target_usd: u64,
-    pub lp_min_threshold_usd: u64,
+    pub lp_min_threshold_usd: u64, // soft cap
+    pub lp_share_bps: u16,         // share of raised SOL paired into the pool
+    pub listing_price_usd: u64,    // micro-USD per MYXN used to size the MYXN side
+    pub amm_program: Pubkey,
+
+    pub lp_mint: Pubkey,
+    pub lp_amount: u64,
+    pub lp_created: bool,
+    pub lp_lock_seconds: i64,    // cliff before any LP can be released
+    pub lp_release_seconds: i64, // linear release after the cliff (0 = all at once)
 
     pub total_raised_usd: u64,
+    pub total_raised_lamports: u64,
+    pub total_raised_spl: [u64; MAX_SPL_MINTS], // indexed like accepted_spl_mints
+    pub excess_lamports_owed: u64,              // unclaimed claim_excess SOL, set at finalize
+    pub excess_spl_owed: [u64; MAX_SPL_MINTS],  // unclaimed claim_excess SPL per mint slot
     pub total_sold: u64,
+    pub total_requested: u64, // tokens bid in an oversubscribed sale, set at finalize
+    pub total_granted: u64,   // presale-vault vesting grants from initialize_vesting
+    pub total_claimed: u64,   // purchases and presale grants paid from the vault
+
+    pub start_ts: i64,
+    pub end_ts: i64,                // moved out by late large buys, see extension_*
+    pub max_end_ts: i64,            // extensions never move end_ts past this
+    pub extension_window_secs: i64, // a buy this close to end_ts can extend the sale
+    pub extension_secs: i64,        // 0 = extensions disabled
+    pub extension_min_usd: u64,     // smallest purchase that triggers an extension
 
     pub is_active: bool,
     pub is_finalized: bool,
     pub refund_enabled: bool,
+    pub guardian: Pubkey,      // may set `paused`; separate from admin
+    pub pending_admin: Pubkey, // proposed by admin, Pubkey::default() = none
+    pub pending_params: Option<ParamsUpdate>, // queued by update_params
+    pub pending_params_eta: i64, // earliest apply_params time
+    pub paused: u8,            // PAUSE_* bitmask
     pub bump: u8,
 }
 
+impl PresaleState {
+    /// Whether the sale window is open and the presale has not been finalized
+    pub fn round_open(&self, now: i64) -> bool {
+        self.is_active && !self.is_finalized && now >= self.start_ts && now < self.end_ts
+    }
+
+    /// Whether the sale can be finalized by anyone: the window has passed or
+    /// the hard cap is sold out (fair launch sales always run to the end)
+    pub fn sale_closed(&self, now: i64) -> bool {
+        now >= self.end_ts || (!self.oversubscription && self.total_sold >= self.presale_tokens)
+    }
+
+    /// Sold and granted tokens not yet claimed from the presale vault
+    pub fn outstanding_tokens(&self) -> Result<u64> {
+        self.total_sold
+            .checked_add(self.total_granted)
+            .and_then(|owed| owed.checked_sub(self.total_claimed))
+            .ok_or(error!(PresaleError::MathOverflow))
+    }
+
+    /// Lamports create_liquidity pairs into the pool
+    pub fn lp_sol_amount(&self) -> u64 {
+        ((self.total_raised_lamports as u128) * (self.lp_share_bps as u128)
+            / BPS_DENOMINATOR as u128) as u64
+    }
+
+    /// Whether finalization pro-rated purchases down to the hard cap
+    pub fn is_oversubscribed(&self) -> bool {
+        self.total_requested > self.presale_tokens
+    }
+
+    /// Share of `amount` kept after pro-rating; `amount` is a requested token
+    /// allocation or a deposit
+    pub fn filled(&self, amount: u64) -> Result<u64> {
+        if self.is_oversubscribed() {
+            mul_div(amount, self.presale_tokens, self.total_requested)
+        } else {
+            Ok(amount)
+        }
+    }
+
+    /// Share of a deposit returned by claim_excess. Rounds down, so buyers'
+    /// excess never sums past the excess of the totals reserved at finalize.
+    pub fn excess(&self, amount: u64) -> Result<u64> {
+        if self.is_oversubscribed() {
+            mul_div(
+                amount,
+                self.total_requested - self.presale_tokens,
+                self.total_requested,
+            )
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Invariants for a parameter change: the price is frozen while the round
+    /// is open, the per-wallet cap and the LP threshold (soft cap) can only
+    /// increase, and the threshold is fixed once finalization has judged the
+    /// raise against it
+    pub fn check_params_update(&self, update: &ParamsUpdate, now: i64) -> Result<()> {
+        if let Some(price_usd) = update.price_usd {
+            require!(price_usd > 0, PresaleError::InvalidParamsUpdate);
+            require!(
+                price_usd == self.price_usd || !self.round_open(now),
+                PresaleError::PriceLockedDuringRound
+            );
+        }
+        if let Some(max_per_wallet_usd) = update.max_per_wallet_usd {
+            require!(
+                max_per_wallet_usd >= self.max_per_wallet_usd,
+                PresaleError::CapDecrease
+            );
+        }
+        if let Some(lp_min_threshold_usd) = update.lp_min_threshold_usd {
+            require!(!self.is_finalized, PresaleError::PresaleFinalized);
+            require!(
+                lp_min_threshold_usd >= self.lp_min_threshold_usd,
+                PresaleError::CapDecrease
+            );
+        }
+        if let Some(fee_split) = update.fee_split {
+            require!(fee_split.is_valid(), PresaleError::InvalidFeeSplit);
+        }
+        Ok(())
+    }
+
+    pub fn require_not_paused(&self, flag: u8) -> Result<()> {
+        require!(self.paused & flag == 0, PresaleError::Paused);
+        Ok(())
+    }
+
+    /// Slot of `mint` in the accepted SPL mint list
+    pub fn spl_mint_slot(&self, mint: &Pubkey) -> Option<usize> {
+        if *mint == Pubkey::default() {
+            return None;
+        }
+        self.accepted_spl_mints.iter().position(|m| m == mint)
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct PurchaseRecord {
@@ -478,21 +3408,405 @@ pub struct PurchaseRecord {
     pub total_tokens: u64,
     pub claimed_tokens: u64,
     pub last_claim_timestamp: i64,
+    pub total_lamports: u64,
+    pub spl_deposits: [u64; MAX_SPL_MINTS], // indexed like PresaleState::accepted_spl_mints
+    pub purchase_count: u32,
+    pub first_purchase_timestamp: i64,
+    pub last_purchase_timestamp: i64,
+    pub excess_sol_claimed: bool,
+    pub excess_spl_claimed: [bool; MAX_SPL_MINTS],
+    pub claim_delegate: Pubkey, // may trigger claims; Pubkey::default() = none
+    pub approved_token_account: Pubkey, // delegated claims pay here; default = any buyer-owned account
+}
+
+impl PurchaseRecord {
+    pub fn has_deposits(&self) -> bool {
+        self.total_lamports > 0 || self.spl_deposits.iter().any(|amount| *amount > 0)
+    }
+
+    /// Mark everything vested by `now` as claimed and return that amount
+    ///
+    /// Purchases vest per the presale-wide schedule from TGE, which is fixed
+    /// at finalization along with any oversubscription pro-rating; callers
+    /// check the presale succeeded.
+    pub fn settle_claim(&mut self, presale: &PresaleState, now: i64) -> Result<u64> {
+        let allocation = presale.filled(self.total_tokens)?;
+        let claimable = presale
+            .vesting_schedule
+            .vested_amount(allocation, presale.tge_timestamp, now)?
+            .checked_sub(self.claimed_tokens)
+            .ok_or(PresaleError::MathOverflow)?;
+        if claimable > 0 {
+            self.claimed_tokens = self
+                .claimed_tokens
+                .checked_add(claimable)
+                .ok_or(PresaleError::MathOverflow)?;
+            self.last_claim_timestamp = now;
+        }
+        Ok(claimable)
+    }
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct VestingAccount {
     pub beneficiary: Pubkey,
+    pub pool: Pubkey, // Pubkey::default() for presale grants
+    pub index: u32,   // distinguishes grants to one beneficiary within a pool
     pub total_amount: u64,
     pub start_timestamp: i64,
-    pub cliff_seconds: u64,
-    pub daily_release_bps: u16, // basis points (e.g., 500 = 5%)
-    pub total_days: u64,
+    pub schedule: VestingSchedule,
     pub claimed_amount: u64,
+    pub revocable: bool, // set at creation; always false for presale grants
+    pub revoked: bool,   // total_amount is frozen at the amount vested when revoked
+    pub claim_delegate: Pubkey, // may trigger claims; Pubkey::default() = none
+    pub approved_token_account: Pubkey, // delegated claims pay here; default = beneficiary-owned
+    pub bump: u8,
+}
+
+impl VestingAccount {
+    /// Amount vested at `now`, fixed at `total_amount` once revoked
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        if self.revoked {
+            return Ok(self.total_amount);
+        }
+        self.schedule
+            .vested_amount(self.total_amount, self.start_timestamp, now)
+    }
+
+    /// Mark everything vested by `now` as claimed and return that amount
+    pub fn settle_claim(&mut self, now: i64) -> Result<u64> {
+        let claimable = self
+            .vested_amount(now)?
+            .checked_sub(self.claimed_amount)
+            .ok_or(PresaleError::MathOverflow)?;
+        self.claimed_amount = self
+            .claimed_amount
+            .checked_add(claimable)
+            .ok_or(PresaleError::MathOverflow)?;
+        Ok(claimable)
+    }
+}
+
+/// Non-presale distribution bucket
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum AllocationKind {
+    Team,
+    Advisors,
+    Marketing,
+    Ecosystem,
+}
+
+/// M-of-N signer set that can act as `PresaleState::admin`
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    pub presale: Pubkey,
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub bump: u8,
+}
+
+impl Multisig {
+    pub fn is_valid_set(signers: &[Pubkey], threshold: u8) -> bool {
+        threshold > 0
+            && threshold as usize <= signers.len()
+            && signers.len() <= MAX_MULTISIG_SIGNERS
+            && signers
+                .iter()
+                .enumerate()
+                .all(|(i, signer)| !signers[..i].contains(signer))
+    }
+
+    /// Whether at least `threshold` distinct members are among `signed`
+    pub fn is_approved(&self, signed: &[Pubkey]) -> bool {
+        let approvals = self
+            .signers
+            .iter()
+            .filter(|member| signed.contains(member))
+            .count();
+        approvals >= self.threshold as usize
+    }
+}
+
+/// Capped token vault from which per-beneficiary vesting grants are paid
+#[account]
+#[derive(InitSpace)]
+pub struct AllocationPool {
+    pub kind: AllocationKind,
+    pub token_mint: Pubkey,
+    pub vault: Pubkey, // ATA of this pool for token_mint
+    pub cap: u64,
+    pub allocated: u64, // sum of grant totals, never above cap
+    pub claimed: u64,
+    pub grant_count: u32,
+    pub bump: u8,
+}
+
+/// Release curve applied to a vesting grant, relative to its start timestamp
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub enum VestingSchedule {
+    /// `tge_unlock_bps` unlocks at start; the remainder releases
+    /// `daily_release_bps` per day after the cliff, fully vested after `total_days`
+    TgeDaily {
+        tge_unlock_bps: u16,
+        cliff_seconds: u64,
+        daily_release_bps: u16, // basis points (e.g., 500 = 5%)
+        total_days: u64,
+    },
+    /// Continuous release over `duration_seconds` from start, nothing before the cliff
+    LinearPerSecond {
+        cliff_seconds: u64,
+        duration_seconds: u64,
+    },
+    /// Equal steps on each calendar-month anniversary of start, nothing before
+    /// `cliff_months`; fully vested after `total_months`
+    MonthlySteps {
+        cliff_months: u16,
+        total_months: u16,
+    },
+    /// Cumulative release points; offsets strictly increasing, last reaches 100%
+    Milestones {
+        #[max_len(MAX_MILESTONES)]
+        milestones: Vec<Milestone>,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct Milestone {
+    pub offset_seconds: u64, // from start_timestamp
+    pub cumulative_bps: u16,
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            VestingSchedule::TgeDaily {
+                tge_unlock_bps,
+                daily_release_bps,
+                ..
+            } => {
+                *tge_unlock_bps as u64 <= BPS_DENOMINATOR
+                    && *daily_release_bps as u64 <= BPS_DENOMINATOR
+                    && (*daily_release_bps > 0 || *tge_unlock_bps as u64 == BPS_DENOMINATOR)
+            }
+            VestingSchedule::LinearPerSecond {
+                cliff_seconds,
+                duration_seconds,
+            } => *duration_seconds > 0 && cliff_seconds <= duration_seconds,
+            VestingSchedule::MonthlySteps {
+                cliff_months,
+                total_months,
+            } => *total_months > 0 && cliff_months <= total_months,
+            VestingSchedule::Milestones { milestones } => {
+                !milestones.is_empty()
+                    && milestones.len() <= MAX_MILESTONES
+                    && milestones.windows(2).all(|w| {
+                        w[0].offset_seconds < w[1].offset_seconds
+                            && w[0].cumulative_bps <= w[1].cumulative_bps
+                    })
+                    && milestones.last().map(|m| m.cumulative_bps as u64) == Some(BPS_DENOMINATOR)
+            }
+        }
+    }
+
+    /// Amount of `total_amount` vested at `now` for a schedule starting at `start_timestamp`
+    pub fn vested_amount(&self, total_amount: u64, start_timestamp: i64, now: i64) -> Result<u64> {
+        if now < start_timestamp {
+            return Ok(0);
+        }
+        let elapsed = (now - start_timestamp) as u64;
+
+        let vested = match self {
+            VestingSchedule::TgeDaily {
+                tge_unlock_bps,
+                cliff_seconds,
+                daily_release_bps,
+                total_days,
+            } => {
+                let tge_amount = mul_div(total_amount, *tge_unlock_bps as u64, BPS_DENOMINATOR)?;
+                if elapsed < *cliff_seconds {
+                    return Ok(tge_amount);
+                }
+
+                let elapsed_days = (elapsed - cliff_seconds) / 86400; // seconds per day
+                if elapsed_days >= *total_days {
+                    return Ok(total_amount); // Fully vested
+                }
+
+                let remaining = (total_amount - tge_amount) as u128;
+                let linear = remaining
+                    .checked_mul(*daily_release_bps as u128)
+                    .and_then(|v| v.checked_mul(elapsed_days as u128))
+                    .ok_or(PresaleError::MathOverflow)?
+                    / BPS_DENOMINATOR as u128;
+
+                tge_amount + linear.min(remaining) as u64
+            }
+            VestingSchedule::LinearPerSecond {
+                cliff_seconds,
+                duration_seconds,
+            } => {
+                if elapsed < *cliff_seconds {
+                    0
+                } else {
+                    mul_div(
+                        total_amount,
+                        elapsed.min(*duration_seconds),
+                        *duration_seconds,
+                    )?
+                }
+            }
+            VestingSchedule::MonthlySteps {
+                cliff_months,
+                total_months,
+            } => {
+                let months = months_elapsed(start_timestamp, now);
+                if months < *cliff_months as u64 {
+                    0
+                } else {
+                    let months = months.min(*total_months as u64);
+                    mul_div(total_amount, months, *total_months as u64)?
+                }
+            }
+            VestingSchedule::Milestones { milestones } => {
+                let bps = milestones
+                    .iter()
+                    .take_while(|m| m.offset_seconds <= elapsed)
+                    .last()
+                    .map_or(0, |m| m.cumulative_bps as u64);
+                mul_div(total_amount, bps, BPS_DENOMINATOR)?
+            }
+        };
+
+        Ok(vested.min(total_amount))
+    }
+}
+
+fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let value = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(PresaleError::MathOverflow)?
+        / denominator as u128;
+    u64::try_from(value).map_err(|_| error!(PresaleError::MathOverflow))
+}
+
+/// Whole calendar months from `start` to `now` (UTC). A step falls on the same
+/// day-of-month and time as `start`, clamped to the last day of shorter months.
+fn months_elapsed(start: i64, now: i64) -> u64 {
+    let (start_day, start_secs) = (start.div_euclid(86400), start.rem_euclid(86400));
+    let (now_day, now_secs) = (now.div_euclid(86400), now.rem_euclid(86400));
+    let (y1, m1, d1) = civil_from_days(start_day);
+    let (y2, m2, d2) = civil_from_days(now_day);
+
+    let mut months = (y2 - y1) * 12 + (m2 as i64 - m1 as i64);
+    let anniversary_day = d1.min(days_in_month(y2, m2));
+    if (d2, now_secs) < (anniversary_day, start_secs) {
+        months -= 1;
+    }
+    months.max(0) as u64
+}
+
+/// (year, month, day) for days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Time-locked LP tokens from `create_liquidity`, releasable to the treasury
+#[account]
+#[derive(InitSpace)]
+pub struct LpLock {
+    pub presale: Pubkey,
+    pub lp_mint: Pubkey,
+    pub vault: Pubkey,
+    pub total_amount: u64,
+    pub released_amount: u64,
+    pub locked_at: i64,
+    pub unlock_timestamp: i64,
+    pub release_end_timestamp: i64,
     pub bump: u8,
 }
 
+impl LpLock {
+    /// Total LP tokens unlocked at `now`, released or not
+    pub fn unlocked_amount(&self, now: i64) -> Result<u64> {
+        if now < self.unlock_timestamp {
+            return Ok(0);
+        }
+        if now >= self.release_end_timestamp {
+            return Ok(self.total_amount);
+        }
+
+        let elapsed = (now - self.unlock_timestamp) as u128;
+        let duration = (self.release_end_timestamp - self.unlock_timestamp) as u128;
+        let unlocked = (self.total_amount as u128)
+            .checked_mul(elapsed)
+            .ok_or(PresaleError::MathOverflow)?
+            / duration;
+
+        Ok(unlocked as u64)
+    }
+}
+
+/// Fee routing split in basis points; must sum to 10,000
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct FeeSplit {
+    pub burn_bps: u16,
+    pub charity_bps: u16,
+    pub liquidity_bps: u16,
+    pub treasury_bps: u16,
+}
+
+impl FeeSplit {
+    pub fn is_valid(&self) -> bool {
+        self.burn_bps as u64
+            + self.charity_bps as u64
+            + self.liquidity_bps as u64
+            + self.treasury_bps as u64
+            == BPS_DENOMINATOR
+    }
+
+    /// Split `amount` into [burn, charity, liquidity, treasury] shares.
+    /// Rounding dust goes to the treasury so the shares always sum to `amount`.
+    pub fn split(&self, amount: u64) -> Result<[u64; 4]> {
+        let share = |bps: u16| -> Result<u64> {
+            let value = (amount as u128)
+                .checked_mul(bps as u128)
+                .ok_or(PresaleError::MathOverflow)?
+                / BPS_DENOMINATOR as u128;
+            Ok(value as u64)
+        };
+
+        let burn = share(self.burn_bps)?;
+        let charity = share(self.charity_bps)?;
+        let liquidity = share(self.liquidity_bps)?;
+        let treasury = amount
+            .checked_sub(burn + charity + liquidity)
+            .ok_or(PresaleError::MathOverflow)?;
+
+        Ok([burn, charity, liquidity, treasury])
+    }
+}
+
 // ============================================================================
 // Parameters
 // ============================================================================
@@ -500,27 +3814,366 @@ pub struct VestingAccount {
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct PresaleParams {
     pub presale_tokens: u64,
+    pub oversubscription: bool,
     pub price_usd: u64,
     pub max_per_wallet_usd: u64,
     pub lp_target_usd: u64,
     pub lp_min_threshold_usd: u64,
+    pub lp_share_bps: u16,
+    pub listing_price_usd: u64,
+    pub amm_program: Pubkey,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub max_end_ts: i64,
+    pub extension_window_secs: i64,
+    pub extension_secs: i64,
+    pub extension_min_usd: u64,
+    pub lp_lock_seconds: i64,
+    pub lp_release_seconds: i64,
     pub burn_wallet: Pubkey,
     pub charity_wallet: Pubkey,
+    pub liquidity_wallet: Pubkey,
+    pub fee_split: FeeSplit,
+    pub accepted_spl_mints: [Pubkey; MAX_SPL_MINTS],
+    pub vesting_schedule: VestingSchedule,
+    pub vesting_transfer_requires_admin: bool,
+    pub guardian: Pubkey,
+    pub sol_price_feed: Pubkey,
+    pub oracle_max_age_secs: u64,
+    pub oracle_max_conf_bps: u16,
+    pub max_slippage_bps: u16,
+}
+
+/// Queued change to post-initialization presale parameters; `None` = unchanged
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct ParamsUpdate {
+    pub price_usd: Option<u64>,
+    pub max_per_wallet_usd: Option<u64>,
+    pub lp_min_threshold_usd: Option<u64>,
+    pub fee_split: Option<FeeSplit>,
+}
+
+/// Proof that (buyer, max_allocation_usd) is a leaf of the whitelist tree
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WhitelistProof {
+    pub max_allocation_usd: u64,
+    pub proof: Vec<[u8; 32]>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct VestingParams {
     pub total_amount: u64,
     pub start_timestamp: i64,
-    pub cliff_seconds: u64,
-    pub daily_release_bps: u16,
-    pub total_days: u64,
+    pub schedule: VestingSchedule,
+    pub revocable: bool, // ignored by initialize_vesting
+}
+
+// ============================================================================
+// Authority
+// ============================================================================
+
+/// Require `signer` to be `authority`, or `authority` to be a multisig
+/// approved by `signer` plus co-signers
+///
+/// In the multisig case the multisig account is the first of `remaining`
+/// and the co-signing members follow it.
+fn require_authority(
+    authority: &Pubkey,
+    signer: &AccountInfo,
+    remaining: &[AccountInfo],
+) -> Result<()> {
+    require!(signer.is_signer, PresaleError::Unauthorized);
+    if signer.key == authority {
+        return Ok(());
+    }
+
+    let (multisig_info, cosigners) = remaining.split_first().ok_or(PresaleError::Unauthorized)?;
+    require!(
+        multisig_info.key == authority && multisig_info.owner == &crate::ID,
+        PresaleError::Unauthorized
+    );
+    let multisig = Multisig::try_deserialize(&mut &multisig_info.try_borrow_data()?[..])?;
+
+    require!(
+        multisig.is_approved(&signed_keys(signer, cosigners)),
+        PresaleError::MultisigThresholdNotMet
+    );
+
+    Ok(())
+}
+
+/// Keys of `signer` and every signing account in `cosigners`
+fn signed_keys(signer: &AccountInfo, cosigners: &[AccountInfo]) -> Vec<Pubkey> {
+    let mut keys = vec![*signer.key];
+    keys.extend(
+        cosigners
+            .iter()
+            .filter(|info| info.is_signer)
+            .map(|info| *info.key),
+    );
+    keys
+}
+
+// ============================================================================
+// Claims
+// ============================================================================
+
+/// Pay `amount` from the presale vault, or from `pool`'s vault when given,
+/// and add it to that source's claimed total
+fn pay_claim<'info>(
+    presale: &mut Account<'info, PresaleState>,
+    pool: Option<&mut Account<'info, AllocationPool>>,
+    vault: &Account<'info, TokenAccount>,
+    to: AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let expected_vault = match pool.as_ref() {
+        Some(pool) => pool.vault,
+        None => get_associated_token_address(&presale.key(), &presale.token_mint),
+    };
+    require!(vault.key() == expected_vault, PresaleError::InvalidVault);
+
+    match pool {
+        Some(pool) => {
+            let seeds = &[
+                b"allocation_pool".as_ref(),
+                &[pool.kind as u8],
+                &[pool.bump],
+            ];
+            let signer = &[&seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: vault.to_account_info(),
+                    to,
+                    authority: pool.to_account_info(),
+                },
+                signer,
+            );
+            token::transfer(transfer_ctx, amount)?;
+
+            pool.claimed = pool
+                .claimed
+                .checked_add(amount)
+                .ok_or(PresaleError::MathOverflow)?;
+        }
+        None => {
+            let seeds = &[b"presale".as_ref(), &[presale.bump]];
+            let signer = &[&seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: vault.to_account_info(),
+                    to,
+                    authority: presale.to_account_info(),
+                },
+                signer,
+            );
+            token::transfer(transfer_ctx, amount)?;
+
+            presale.total_claimed = presale
+                .total_claimed
+                .checked_add(amount)
+                .ok_or(PresaleError::MathOverflow)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create `beneficiary`'s ATA for `mint`, paid by the fee reserve while it
+/// stays rent-exempt afterwards and by the cranker otherwise
+#[allow(clippy::too_many_arguments)]
+fn create_beneficiary_ata<'info>(
+    fee_reserve: &AccountInfo<'info>,
+    fee_reserve_bump: u8,
+    cranker: &Signer<'info>,
+    associated_token: &AccountInfo<'info>,
+    beneficiary: &AccountInfo<'info>,
+    mint: &Account<'info, Mint>,
+    associated_token_program: &Program<'info, AssociatedToken>,
+    token_program: &Program<'info, Token>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let rent = Rent::get()?;
+    let reserve_covers = fee_reserve.lamports()
+        >= rent
+            .minimum_balance(TokenAccount::LEN)
+            .saturating_add(rent.minimum_balance(0));
+
+    let payer = if reserve_covers {
+        fee_reserve.to_account_info()
+    } else {
+        cranker.to_account_info()
+    };
+    let reserve_seeds = &[b"fee_reserve".as_ref(), &[fee_reserve_bump]];
+    let reserve_signer = &[&reserve_seeds[..]];
+    associated_token::create(CpiContext::new_with_signer(
+        associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer,
+            associated_token: associated_token.to_account_info(),
+            authority: beneficiary.to_account_info(),
+            mint: mint.to_account_info(),
+            system_program: system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+        reserve_signer,
+    ))
+}
+
+// ============================================================================
+// Oracle
+// ============================================================================
+
+/// Load the SOL/USD price, rejecting stale or low-confidence updates
+fn load_sol_price(price_feed: &AccountInfo, max_age_secs: u64, max_conf_bps: u16) -> Result<Price> {
+    let feed = load_price_feed_from_account_info(price_feed)
+        .map_err(|_| error!(PresaleError::InvalidOracleAccount))?;
+    let now = Clock::get()?.unix_timestamp;
+    let price = feed
+        .get_price_no_older_than(now, max_age_secs)
+        .ok_or(PresaleError::OracleStale)?;
+
+    require!(price.price > 0, PresaleError::InvalidOraclePrice);
+    require!(
+        (price.conf as u128) * (BPS_DENOMINATOR as u128)
+            <= (price.price as u128) * (max_conf_bps as u128),
+        PresaleError::OracleConfidenceTooWide
+    );
+
+    Ok(price)
+}
+
+/// Convert lamports to micro-USD: lamports * price * 10^expo * USD_SCALE / LAMPORTS_PER_SOL
+fn lamports_to_usd(lamports: u64, price: &Price) -> Result<u64> {
+    let mut numerator = (lamports as u128)
+        .checked_mul(price.price as u128)
+        .and_then(|v| v.checked_mul(USD_SCALE as u128))
+        .ok_or(PresaleError::MathOverflow)?;
+    let mut denominator = LAMPORTS_PER_SOL as u128;
+
+    if price.expo >= 0 {
+        numerator = numerator
+            .checked_mul(10u128.pow(price.expo as u32))
+            .ok_or(PresaleError::MathOverflow)?;
+    } else {
+        denominator = denominator
+            .checked_mul(10u128.pow(price.expo.unsigned_abs()))
+            .ok_or(PresaleError::MathOverflow)?;
+    }
+
+    u64::try_from(numerator / denominator).map_err(|_| error!(PresaleError::MathOverflow))
+}
+
+/// Whether `quoted` is within `max_slippage_bps` of `actual`
+fn within_slippage(actual: u64, quoted: u64, max_slippage_bps: u16) -> bool {
+    let diff = actual.abs_diff(quoted) as u128;
+    diff * (BPS_DENOMINATOR as u128) <= (actual as u128) * (max_slippage_bps as u128)
+}
+
+// ============================================================================
+// AMM
+// ============================================================================
+
+/// Build the AMM `add_liquidity(amount_a, amount_b, min_lp_out)` instruction.
+///
+/// Uses the Anchor sighash for `global:add_liquidity` and the account order of
+/// `mock_amm::AddLiquidity`; adapters for Raydium/Orca expose the same interface.
+fn amm_add_liquidity_ix(
+    amm_program: Pubkey,
+    accounts: &CreateLiquidity,
+    amount_a: u64,
+    amount_b: u64,
+    min_lp_out: u64,
+) -> Instruction {
+    let mut data = hash::hash(b"global:add_liquidity").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&amount_a.to_le_bytes());
+    data.extend_from_slice(&amount_b.to_le_bytes());
+    data.extend_from_slice(&min_lp_out.to_le_bytes());
+
+    Instruction {
+        program_id: amm_program,
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.pool.key(), false),
+            AccountMeta::new_readonly(accounts.presale_state.key(), true),
+            AccountMeta::new(accounts.wsol_account.key(), false),
+            AccountMeta::new(accounts.vault.key(), false),
+            AccountMeta::new(accounts.pool_vault_a.key(), false),
+            AccountMeta::new(accounts.pool_vault_b.key(), false),
+            AccountMeta::new(accounts.lp_mint.key(), false),
+            AccountMeta::new(accounts.lp_lock_vault.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        data,
+    }
+}
+
+// ============================================================================
+// Whitelist
+// ============================================================================
+
+/// Verify a sorted-pair keccak Merkle proof for the leaf (buyer, max_allocation_usd).
+/// Leaves and inner nodes are domain-separated with a 0x00 / 0x01 prefix.
+fn verify_whitelist_proof(
+    root: &[u8; 32],
+    buyer: &Pubkey,
+    max_allocation_usd: u64,
+    proof: &[[u8; 32]],
+) -> bool {
+    let mut node = keccak::hashv(&[&[0u8], buyer.as_ref(), &max_allocation_usd.to_le_bytes()]).0;
+
+    for sibling in proof {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        node = keccak::hashv(&[&[1u8], &left, &right]).0;
+    }
+
+    node == *root
 }
 
 // ============================================================================
 // Events
 // ============================================================================
 
+#[event]
+pub struct PurchaseRecorded {
+    pub buyer: Pubkey,
+    pub purchase_index: u32,
+    pub amount_usd: u64,
+    pub sol_amount: u64,
+    pub spl_amount: u64,
+    pub tokens: u64,
+    pub total_purchased_usd: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SaleExtended {
+    pub buyer: Pubkey,
+    pub purchase_usd: u64,
+    pub end_ts: i64,
+}
+
+#[event]
+pub struct PresaleOversubscribed {
+    pub total_requested: u64,
+    pub presale_tokens: u64,
+    pub total_raised: u64,
+}
+
+#[event]
+pub struct ExcessClaimed {
+    pub buyer: Pubkey,
+    pub lamports: u64,
+    pub spl_mint: Option<Pubkey>,
+    pub spl_amount: u64,
+}
+
 #[event]
 pub struct PresaleSuccess {
     pub total_raised: u64,
@@ -533,6 +4186,168 @@ pub struct PresaleFailed {
     pub threshold: u64,
 }
 
+#[event]
+pub struct LiquidityCreated {
+    pub lp_mint: Pubkey,
+    pub lp_amount: u64,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+}
+
+#[event]
+pub struct LiquidityLocked {
+    pub lp_mint: Pubkey,
+    pub amount: u64,
+    pub unlock_timestamp: i64,
+    pub release_end_timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityUnlocked {
+    pub lp_mint: Pubkey,
+    pub amount: u64,
+    pub total_released: u64,
+}
+
+#[event]
+pub struct VaultFunded {
+    pub amount: u64,
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct SolvencyChecked {
+    pub vault_balance: u64,
+    pub outstanding: u64,
+    pub solvent: bool,
+}
+
+#[event]
+pub struct WhitelistUpdated {
+    pub whitelist_root: [u8; 32],
+    pub whitelist_only: bool,
+}
+
+#[event]
+pub struct AllocationPoolCreated {
+    pub pool: Pubkey,
+    pub kind: AllocationKind,
+    pub cap: u64,
+}
+
+#[event]
+pub struct PoolVestingCreated {
+    pub pool: Pubkey,
+    pub beneficiary: Pubkey,
+    pub index: u32,
+    pub total_amount: u64,
+    pub start_timestamp: i64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub vesting_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub vested_amount: u64,
+    pub clawed_back: u64,
+}
+
+#[event]
+pub struct VestingTransferred {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub vesting_moved: bool,
+    pub purchase_moved: bool,
+}
+
+#[event]
+pub struct VestingClaimed {
+    pub vesting_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+}
+
+#[event]
+pub struct CrankClaimed {
+    pub beneficiary: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub ata_created: bool,
+}
+
+#[event]
+pub struct ClaimDelegateSet {
+    pub vesting_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub claim_delegate: Pubkey,
+    pub approved_token_account: Pubkey,
+}
+
+#[event]
+pub struct PurchaseClaimDelegateSet {
+    pub purchase_record: Pubkey,
+    pub buyer: Pubkey,
+    pub claim_delegate: Pubkey,
+    pub approved_token_account: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub guardian: Pubkey,
+    pub previous: u8,
+    pub paused: u8,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub previous: Pubkey,
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub previous: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct MultisigUpdated {
+    pub multisig: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ParamsUpdateQueued {
+    pub update: ParamsUpdate,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ParamsUpdateApplied {
+    pub update: ParamsUpdate,
+}
+
+#[event]
+pub struct ParamsUpdateCancelled {}
+
+#[event]
+pub struct FeesDistributed {
+    pub mint: Option<Pubkey>, // None for SOL
+    pub total_amount: u64,
+    pub burn_amount: u64,
+    pub charity_amount: u64,
+    pub liquidity_amount: u64,
+    pub treasury_amount: u64,
+}
+
 // ============================================================================
 // Errors
 // ============================================================================
@@ -571,4 +4386,312 @@ pub enum PresaleError {
 
     #[msg("Tokens already claimed")]
     AlreadyClaimed,
+
+    #[msg("Fee split must sum to 10000 basis points")]
+    InvalidFeeSplit,
+
+    #[msg("Presale is not finalized")]
+    PresaleNotFinalized,
+
+    #[msg("Refunds are active")]
+    RefundsActive,
+
+    #[msg("Nothing to distribute")]
+    NothingToDistribute,
+
+    #[msg("Fee token accounts missing for SPL distribution")]
+    FeeTokenAccountMissing,
+
+    #[msg("Fee recipient does not match presale configuration")]
+    InvalidFeeRecipient,
+
+    #[msg("Payment token accounts missing for SPL payment")]
+    PaymentAccountMissing,
+
+    #[msg("Unsupported SPL mint for payment")]
+    UnsupportedSplMint,
+
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+
+    #[msg("Invalid oracle price account")]
+    InvalidOracleAccount,
+
+    #[msg("Oracle price is stale")]
+    OracleStale,
+
+    #[msg("Oracle price is invalid")]
+    InvalidOraclePrice,
+
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+
+    #[msg("Quoted USD value outside allowed slippage")]
+    SlippageExceeded,
+
+    #[msg("Whitelist proof required")]
+    WhitelistProofRequired,
+
+    #[msg("Invalid whitelist proof")]
+    InvalidWhitelistProof,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("Vault balance does not cover outstanding tokens")]
+    VaultInsolvent,
+
+    #[msg("Invalid LP configuration")]
+    InvalidLpConfig,
+
+    #[msg("Liquidity already created")]
+    LiquidityAlreadyCreated,
+
+    #[msg("Raise below LP threshold")]
+    LpThresholdNotMet,
+
+    #[msg("AMM program does not match presale configuration")]
+    InvalidAmmProgram,
+
+    #[msg("Liquidity is still locked")]
+    LiquidityStillLocked,
+
+    #[msg("LP lock vault mismatch")]
+    InvalidLpLockVault,
+
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+
+    #[msg("No vesting schedule supplied")]
+    NoVestingSchedule,
+
+    #[msg("TGE timestamp cannot be in the past")]
+    InvalidTgeTimestamp,
+
+    #[msg("Grant exceeds allocation pool cap")]
+    PoolCapExceeded,
+
+    #[msg("Vesting schedule is not revocable")]
+    NotRevocable,
+
+    #[msg("Vesting schedule already revoked")]
+    AlreadyRevoked,
+
+    #[msg("Invalid beneficiary transfer")]
+    InvalidBeneficiary,
+
+    #[msg("Vault does not match the claim source")]
+    InvalidVault,
+
+    #[msg("Vesting account passed more than once")]
+    DuplicateVestingAccount,
+
+    #[msg("Destination is not approved by the beneficiary")]
+    UnapprovedDestination,
+
+    #[msg("Instruction is paused")]
+    Paused,
+
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("No admin handover pending")]
+    NoPendingAdmin,
+
+    #[msg("Invalid multisig signer set or threshold")]
+    InvalidMultisig,
+
+    #[msg("Not enough multisig members signed")]
+    MultisigThresholdNotMet,
+
+    #[msg("No parameter update queued")]
+    NoPendingParams,
+
+    #[msg("Parameter update timelock has not elapsed")]
+    ParamsTimelockActive,
+
+    #[msg("Invalid parameter update")]
+    InvalidParamsUpdate,
+
+    #[msg("Price cannot change while the round is open")]
+    PriceLockedDuringRound,
+
+    #[msg("Caps can only increase")]
+    CapDecrease,
+
+    #[msg("Invalid sale window")]
+    InvalidSaleWindow,
+
+    #[msg("Sale has not started")]
+    SaleNotStarted,
+
+    #[msg("Sale has ended")]
+    SaleEnded,
+
+    #[msg("Sale is still open")]
+    SaleStillOpen,
+
+    #[msg("Presale was not oversubscribed")]
+    NotOversubscribed,
+
+    #[msg("Excess already claimed for this deposit")]
+    ExcessAlreadyClaimed,
+
+    #[msg("Amount would spend excess deposits or the LP share held in escrow")]
+    ExcessReserved,
+
+    #[msg("Fee source must be the presale escrow of an accepted SPL mint")]
+    InvalidFeeEscrow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+    const TOTAL: u64 = 1_200_000;
+    // 2024-01-31T12:00:00Z
+    const JAN_31: i64 = 1_706_702_400;
+
+    #[test]
+    fn nothing_vests_before_start() {
+        let schedule = VestingSchedule::LinearPerSecond {
+            cliff_seconds: 0,
+            duration_seconds: 100,
+        };
+        assert_eq!(schedule.vested_amount(TOTAL, 1_000, 999).unwrap(), 0);
+    }
+
+    #[test]
+    fn tge_daily_unlocks_tge_then_daily() {
+        let schedule = VestingSchedule::TgeDaily {
+            tge_unlock_bps: 1_000,
+            cliff_seconds: 30 * DAY as u64,
+            daily_release_bps: 100,
+            total_days: 100,
+        };
+        assert!(schedule.is_valid());
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 0).unwrap(), 120_000);
+        assert_eq!(
+            schedule.vested_amount(TOTAL, 0, 30 * DAY - 1).unwrap(),
+            120_000
+        );
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 40 * DAY).unwrap(), 228_000);
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 130 * DAY).unwrap(), TOTAL);
+    }
+
+    #[test]
+    fn linear_per_second_respects_cliff() {
+        let schedule = VestingSchedule::LinearPerSecond {
+            cliff_seconds: 100,
+            duration_seconds: 1_000,
+        };
+        assert!(schedule.is_valid());
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 99).unwrap(), 0);
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 100).unwrap(), 120_000);
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 501).unwrap(), 601_200);
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 5_000).unwrap(), TOTAL);
+    }
+
+    #[test]
+    fn monthly_steps_follow_calendar_months() {
+        let schedule = VestingSchedule::MonthlySteps {
+            cliff_months: 1,
+            total_months: 12,
+        };
+        assert!(schedule.is_valid());
+        // 2024-02-29T11:59:59Z: anniversary clamps to Feb 29 at noon
+        assert_eq!(
+            schedule
+                .vested_amount(TOTAL, JAN_31, JAN_31 + 29 * DAY - 1)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            schedule
+                .vested_amount(TOTAL, JAN_31, JAN_31 + 29 * DAY)
+                .unwrap(),
+            100_000
+        );
+        // 2024-03-30T12:00:00Z is still within the second month
+        assert_eq!(
+            schedule
+                .vested_amount(TOTAL, JAN_31, JAN_31 + 59 * DAY)
+                .unwrap(),
+            100_000
+        );
+        // 2024-03-31T12:00:00Z
+        assert_eq!(
+            schedule
+                .vested_amount(TOTAL, JAN_31, JAN_31 + 60 * DAY)
+                .unwrap(),
+            200_000
+        );
+        assert_eq!(
+            schedule
+                .vested_amount(TOTAL, JAN_31, JAN_31 + 400 * DAY)
+                .unwrap(),
+            TOTAL
+        );
+    }
+
+    #[test]
+    fn milestones_step_at_offsets() {
+        let schedule = VestingSchedule::Milestones {
+            milestones: vec![
+                Milestone {
+                    offset_seconds: 0,
+                    cumulative_bps: 2_500,
+                },
+                Milestone {
+                    offset_seconds: 1_000,
+                    cumulative_bps: 6_000,
+                },
+                Milestone {
+                    offset_seconds: 2_000,
+                    cumulative_bps: 10_000,
+                },
+            ],
+        };
+        assert!(schedule.is_valid());
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 0).unwrap(), 300_000);
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 999).unwrap(), 300_000);
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 1_500).unwrap(), 720_000);
+        assert_eq!(schedule.vested_amount(TOTAL, 0, 2_000).unwrap(), TOTAL);
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        let incomplete = VestingSchedule::Milestones {
+            milestones: vec![Milestone {
+                offset_seconds: 0,
+                cumulative_bps: 9_000,
+            }],
+        };
+        let unordered = VestingSchedule::Milestones {
+            milestones: vec![
+                Milestone {
+                    offset_seconds: 10,
+                    cumulative_bps: 5_000,
+                },
+                Milestone {
+                    offset_seconds: 10,
+                    cumulative_bps: 10_000,
+                },
+            ],
+        };
+        assert!(!incomplete.is_valid());
+        assert!(!unordered.is_valid());
+        assert!(!VestingSchedule::Milestones { milestones: vec![] }.is_valid());
+        assert!(!VestingSchedule::LinearPerSecond {
+            cliff_seconds: 10,
+            duration_seconds: 0,
+        }
+        .is_valid());
+        assert!(!VestingSchedule::MonthlySteps {
+            cliff_months: 13,
+            total_months: 12,
+        }
+        .is_valid());
+    }
 }